@@ -0,0 +1,148 @@
+use crate::app::App;
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Stacking order change for the selection. Shapes later in
+/// `Canvas::shapes` are drawn on top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZOrder {
+    Forward,
+    Backward,
+    ToFront,
+    ToBack,
+}
+
+impl ZOrder {
+    fn done_message(self) -> &'static str {
+        match self {
+            ZOrder::Forward => "Brought forward",
+            ZOrder::Backward => "Sent backward",
+            ZOrder::ToFront => "Brought to front",
+            ZOrder::ToBack => "Sent to back",
+        }
+    }
+}
+
+/// New stacking order as old indices, bottom first. Selected shapes keep
+/// their order among themselves; one step moves each selected run past
+/// the neighbouring unselected shape.
+pub fn restack(len: usize, selected: &HashSet<usize>, order: ZOrder) -> Vec<usize> {
+    let mut stack: Vec<usize> = (0..len).collect();
+    let is_selected = |idx: usize| selected.contains(&idx);
+    match order {
+        ZOrder::ToFront | ZOrder::ToBack => {
+            let (chosen, rest): (Vec<usize>, Vec<usize>) =
+                stack.into_iter().partition(|&idx| is_selected(idx));
+            if order == ZOrder::ToFront {
+                stack = rest;
+                stack.extend(chosen);
+            } else {
+                stack = chosen;
+                stack.extend(rest);
+            }
+        }
+        ZOrder::Forward => {
+            for i in (0..len.saturating_sub(1)).rev() {
+                if is_selected(stack[i]) && !is_selected(stack[i + 1]) {
+                    stack.swap(i, i + 1);
+                }
+            }
+        }
+        ZOrder::Backward => {
+            for i in 1..len {
+                if is_selected(stack[i]) && !is_selected(stack[i - 1]) {
+                    stack.swap(i, i - 1);
+                }
+            }
+        }
+    }
+    stack
+}
+
+impl App {
+    /// Move the selected shapes up or down the stack as a single undo step.
+    pub fn restack_selection(&mut self, order: ZOrder) {
+        let len = self.canvas.shapes.len();
+        let selected: HashSet<usize> = self
+            .selected_shape_indices
+            .iter()
+            .copied()
+            .filter(|&idx| idx < len)
+            .collect();
+        if selected.is_empty() {
+            return;
+        }
+        let stack = restack(len, &selected, order);
+        if stack.iter().enumerate().all(|(new, &old)| new == old) {
+            return;
+        }
+
+        self.canvas.push_history();
+        let mut new_index = vec![0; len];
+        for (new, &old) in stack.iter().enumerate() {
+            new_index[old] = new;
+        }
+        let mut old_shapes: Vec<Option<_>> =
+            std::mem::take(&mut self.canvas.shapes).into_iter().map(Some).collect();
+        self.canvas.shapes = stack
+            .iter()
+            .filter_map(|&old| old_shapes[old].take())
+            .collect();
+
+        self.selected_shape_indices = selected.iter().map(|&old| new_index[old]).collect();
+        self.primary_selected = self
+            .primary_selected
+            .filter(|&idx| idx < len)
+            .map(|idx| new_index[idx]);
+        self.editing_text_index = self
+            .editing_text_index
+            .filter(|&idx| idx < len)
+            .map(|idx| new_index[idx]);
+        self.is_dirty = true;
+        self.notification = Some((order.done_message().to_string(), Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui;
+
+    fn set(indices: &[usize]) -> HashSet<usize> {
+        indices.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_restack_keeps_relative_order() {
+        let selected = set(&[1, 3]);
+        assert_eq!(restack(5, &selected, ZOrder::ToFront), vec![0, 2, 4, 1, 3]);
+        assert_eq!(restack(5, &selected, ZOrder::ToBack), vec![1, 3, 0, 2, 4]);
+        assert_eq!(restack(5, &selected, ZOrder::Forward), vec![0, 2, 1, 4, 3]);
+        assert_eq!(restack(5, &selected, ZOrder::Backward), vec![1, 0, 3, 2, 4]);
+        // Already on top: nothing moves
+        assert_eq!(restack(3, &set(&[1, 2]), ZOrder::Forward), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_restack_selection_follows_shapes_and_undoes_in_one_step() {
+        let mut app = App::default();
+        for (i, name) in ["A", "B", "C"].iter().enumerate() {
+            app.canvas
+                .add_text(egui::pos2(i as f32 * 50.0, 0.0), name.to_string(), egui::Color32::WHITE);
+        }
+        let ids: Vec<usize> = app.canvas.shapes.iter().map(|s| s.id).collect();
+        let history_len = app.canvas.history.len();
+        app.select_single(0);
+
+        app.restack_selection(ZOrder::ToFront);
+        let order: Vec<usize> = app.canvas.shapes.iter().map(|s| s.id).collect();
+        assert_eq!(order, vec![ids[1], ids[2], ids[0]]);
+        assert_eq!(app.selected_shape_indices, set(&[2]));
+        assert_eq!(app.primary_selected, Some(2));
+        assert_eq!(app.canvas.history.len(), history_len + 1);
+
+        app.canvas.undo();
+        let order: Vec<usize> = app.canvas.shapes.iter().map(|s| s.id).collect();
+        assert_eq!(order, ids);
+    }
+}
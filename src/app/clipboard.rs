@@ -0,0 +1,150 @@
+use crate::app::App;
+use crate::shapes::{Shape, Tool};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// First line of the clipboard text written for copied shapes, so other
+/// Kugel windows recognize it and ordinary text is never mistaken for it.
+const CLIPBOARD_MARKER: &str = "kugel-shapes/1";
+
+/// Copied shapes with their canvas positions and embedded image bytes.
+#[derive(Serialize, Deserialize)]
+struct ClipboardPayload {
+    shapes: Vec<Shape>,
+}
+
+pub fn encode_shapes(shapes: &[Shape]) -> Result<String, String> {
+    let payload = ClipboardPayload {
+        shapes: shapes.to_vec(),
+    };
+    let json = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+    Ok(format!("{}\n{}", CLIPBOARD_MARKER, json))
+}
+
+/// Shapes from clipboard text written by `encode_shapes`, or `None` for any
+/// other text.
+pub fn decode_shapes(text: &str) -> Option<Vec<Shape>> {
+    let json = text.strip_prefix(CLIPBOARD_MARKER)?.trim_start();
+    let payload: ClipboardPayload = serde_json::from_str(json).ok()?;
+    (!payload.shapes.is_empty()).then_some(payload.shapes)
+}
+
+impl App {
+    /// The system clipboard, opened once and kept open: on Linux the contents
+    /// we set disappear when the last `Clipboard` is dropped.
+    pub fn system_clipboard(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.clipboard.as_mut().expect("clipboard was just opened"))
+    }
+
+    /// Put the whole selection on the system clipboard. When the clipboard
+    /// is unavailable the shapes are kept for pasting within this window.
+    pub fn copy_selection(&mut self) {
+        let shapes = self.selected_shapes();
+        if shapes.is_empty() {
+            return;
+        }
+        let count = shapes.len();
+        let written = encode_shapes(&shapes).and_then(|text| {
+            self.system_clipboard()?
+                .set_text(text)
+                .map_err(|e| e.to_string())
+        });
+        let message = match written {
+            Ok(()) => {
+                self.copied_shapes.clear();
+                format!("Copied {} shape(s)", count)
+            }
+            Err(_) => {
+                self.copied_shapes = shapes;
+                format!("Copied {} shape(s) to buffer", count)
+            }
+        };
+        self.notification = Some((message, Instant::now()));
+    }
+
+    /// Paste shapes copied in any Kugel window, centered on the cursor, as a
+    /// single undo step. Returns false when the clipboard holds no shapes.
+    pub fn try_paste_shapes(&mut self, ctx: &egui::Context) -> bool {
+        // Text, or empty when the clipboard holds something else; `None` only
+        // when the clipboard could not be read at all.
+        let clipboard_text = match self.system_clipboard() {
+            Ok(clipboard) => match clipboard.get_text() {
+                Ok(text) => Some(text),
+                Err(arboard::Error::ContentNotAvailable) => Some(String::new()),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+        // The buffer only stands in for an unreadable clipboard; whatever the
+        // clipboard holds now is newer than it.
+        let shapes = match clipboard_text {
+            Some(text) => {
+                self.copied_shapes.clear();
+                match decode_shapes(&text) {
+                    Some(shapes) => shapes,
+                    None => return false,
+                }
+            }
+            None if !self.copied_shapes.is_empty() => self.copied_shapes.clone(),
+            None => return false,
+        };
+
+        let target = self.paste_target_canvas(ctx);
+        let inserted = self.canvas.insert_shapes(shapes, target, ctx);
+        let count = inserted.len();
+        self.clear_selection();
+        self.selected_shape_indices.extend(inserted.clone());
+        self.primary_selected = inserted.last();
+        self.tool = Tool::Select;
+        self.is_dirty = true;
+        self.generate_missing_link_previews(ctx);
+        self.notification = Some((format!("Pasted {} shape(s)", count), Instant::now()));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_round_trip_keeps_positions_and_images() {
+        let shapes = vec![
+            Shape::new_rect(
+                3,
+                egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(30.0, 40.0)),
+                egui::Color32::RED,
+                2.0,
+                false,
+            ),
+            Shape::new_image(
+                4,
+                egui::Rect::from_min_size(egui::pos2(100.0, 0.0), egui::vec2(8.0, 8.0)),
+                vec![1, 2, 3].into(),
+                [8.0, 8.0],
+                None,
+            ),
+        ];
+        let text = encode_shapes(&shapes).unwrap();
+        assert!(text.starts_with(CLIPBOARD_MARKER));
+
+        let decoded = decode_shapes(&text).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].data.get_bounds(), shapes[0].data.get_bounds());
+        let crate::shapes::ShapeData::Image { bytes, .. } = &decoded[1].data else {
+            panic!("expected an image");
+        };
+        assert_eq!(&bytes[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_decode_ignores_other_text() {
+        assert!(decode_shapes("hello").is_none());
+        assert!(decode_shapes("kugel-shapes/1\nnot json").is_none());
+        assert!(decode_shapes("kugel-shapes/1\n{\"shapes\":[]}").is_none());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::app::App;
+use crate::export::tiles::{check_copy_limits, check_pixel_limits, plan_bands};
+use crate::export::{
+    render_rgba, section_title, sections_in_reading_order, write_target, ExportTarget,
+};
+use crate::shapes::{Shape, ShapeData};
+use crate::updater::UiEvent;
+use eframe::egui;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Canvas units around the selection for Copy as PNG.
+const COPY_PADDING: f32 = 16.0;
+
+/// Part of the board an export covers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    Board,
+    Selection,
+    /// Section box with the given shape id, clipped to its rect.
+    Section(usize),
+    /// What is visible on screen at the current zoom.
+    Viewport,
+}
+
+impl ExportScope {
+    /// Whether the padding setting applies; sections and the viewport are
+    /// exported at their exact rect.
+    pub fn uses_padding(self) -> bool {
+        matches!(self, ExportScope::Board | ExportScope::Selection)
+    }
+}
+
+/// File names for a batch of section exports: the section title made safe for
+/// file systems, or `Section N` when it has none, made unique with a counter.
+pub fn section_file_names(titles: &[Option<String>], ext: &str) -> Vec<String> {
+    let mut used = HashSet::new();
+    titles
+        .iter()
+        .enumerate()
+        .map(|(i, title)| {
+            let cleaned: String = title
+                .as_deref()
+                .unwrap_or_default()
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .take(80)
+                .collect();
+            let cleaned = cleaned.trim().trim_matches('.').to_string();
+            let base = if cleaned.is_empty() {
+                format!("Section {}", i + 1)
+            } else {
+                cleaned
+            };
+            let mut name = format!("{}.{}", base, ext);
+            let mut n = 2;
+            while !used.insert(name.to_lowercase()) {
+                name = format!("{} ({}).{}", base, n, ext);
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+impl App {
+    /// Canvas rect currently visible on screen.
+    pub fn visible_canvas_rect(&self, ctx: &egui::Context) -> egui::Rect {
+        let screen = ctx.screen_rect();
+        egui::Rect::from_min_max(
+            self.screen_to_canvas(screen.min),
+            self.screen_to_canvas(screen.max),
+        )
+    }
+
+    /// Copies of the selected shapes in drawing order.
+    pub fn selected_shapes(&self) -> Vec<Shape> {
+        let mut indices: Vec<usize> = self.selected_shape_indices.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .filter_map(|idx| self.canvas.shapes.get(idx).cloned())
+            .collect()
+    }
+
+    pub fn export_target(&self, ctx: &egui::Context) -> Result<ExportTarget, String> {
+        match self.export_scope {
+            ExportScope::Board | ExportScope::Selection => {
+                let shapes = if self.export_scope == ExportScope::Selection {
+                    if !self.has_selection() {
+                        return Err("Nothing is selected".to_string());
+                    }
+                    self.selected_shapes()
+                } else {
+                    self.canvas.shapes.clone()
+                };
+                ExportTarget::content(
+                    shapes,
+                    self.export_settings.padding,
+                    self.export_settings.scale,
+                )
+            }
+            ExportScope::Section(id) => {
+                let region = self
+                    .canvas
+                    .shapes
+                    .iter()
+                    .find_map(|shape| match &shape.data {
+                        ShapeData::SectionBox { rect, .. } if shape.id == id => Some(*rect),
+                        _ => None,
+                    })
+                    .ok_or_else(|| "The chosen section no longer exists".to_string())?;
+                Ok(ExportTarget::clipped(
+                    &self.canvas.shapes,
+                    region,
+                    self.export_settings.scale,
+                ))
+            }
+            ExportScope::Viewport => Ok(ExportTarget::clipped(
+                &self.canvas.shapes,
+                self.visible_canvas_rect(ctx),
+                self.export_settings.scale * self.zoom,
+            )),
+        }
+    }
+
+    /// Write every section to its own file in a chosen folder, in the
+    /// background, reporting tile progress across all sections in the
+    /// export dialog like a single export.
+    pub fn export_sections_dialog(&mut self, ctx: &egui::Context) {
+        if self.export_in_flight {
+            self.notification = Some(("An export is already running".to_string(), Instant::now()));
+            return;
+        }
+        let sections = sections_in_reading_order(&self.canvas.shapes);
+        if sections.is_empty() {
+            self.notification = Some((
+                "The board has no sections to export".to_string(),
+                Instant::now(),
+            ));
+            return;
+        }
+        let Some(folder) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+
+        let settings = self.export_settings.clone();
+        let titles: Vec<Option<String>> = sections
+            .iter()
+            .map(|(_, rect)| section_title(&self.canvas.shapes, *rect))
+            .collect();
+        let jobs: Vec<(ExportTarget, PathBuf)> = sections
+            .iter()
+            .zip(section_file_names(&titles, settings.format.extension()))
+            .map(|((_, rect), name)| {
+                (
+                    ExportTarget::clipped(&self.canvas.shapes, *rect, self.export_settings.scale),
+                    folder.join(name),
+                )
+            })
+            .collect();
+
+        if !settings.format.is_vector() {
+            if let Some(e) = jobs.iter().find_map(|(target, _)| {
+                check_pixel_limits(settings.format, target.pixel_size()).err()
+            }) {
+                self.notification = Some((format!("Section export failed: {}", e), Instant::now()));
+                return;
+            }
+        }
+
+        let existing: Vec<String> = jobs
+            .iter()
+            .filter(|(_, path)| path.exists())
+            .map(|(_, path)| path.file_name().unwrap_or_default().to_string_lossy().to_string())
+            .collect();
+        if !existing.is_empty() {
+            let confirm = rfd::MessageDialog::new()
+                .set_title("Replace Existing Files")
+                .set_description(format!(
+                    "{} file(s) in the chosen folder will be replaced: {}. Replace them?",
+                    existing.len(),
+                    existing.join(", ")
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm != rfd::MessageDialogResult::Yes {
+                return;
+            }
+        }
+
+        let bg = self.background_color;
+        let ui_tx = self.ui_event_tx.clone();
+        let ctx = ctx.clone();
+        self.export_in_flight = true;
+        self.export_progress = None;
+        std::thread::spawn(move || {
+            let tiles = |target: &ExportTarget| {
+                let [width, height] = target.pixel_size();
+                plan_bands(width, height).iter().map(|band| band.tiles.len()).sum::<usize>()
+            };
+            let total_tiles: usize = jobs.iter().map(|(target, _)| tiles(target)).sum();
+            let mut tiles_before = 0;
+            let mut errors = Vec::new();
+            for (target, path) in &jobs {
+                let progress_tx = ui_tx.clone();
+                let progress_ctx = ctx.clone();
+                let result = write_target(target, &settings, bg, path, &mut |done, _| {
+                    let _ = progress_tx.send(UiEvent::ExportProgress {
+                        done: tiles_before + done,
+                        total: total_tiles,
+                    });
+                    progress_ctx.request_repaint();
+                });
+                if let Err(e) = result {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    errors.push(format!("{}: {}", name, e));
+                }
+                if !settings.format.is_vector() {
+                    tiles_before += tiles(target);
+                }
+            }
+
+            let total = jobs.len();
+            let folder_name = folder
+                .file_name()
+                .unwrap_or(folder.as_os_str())
+                .to_string_lossy()
+                .to_string();
+            let result = match errors.first() {
+                None => Ok(format!("Exported {total} section(s) to {folder_name}")),
+                Some(first) => Err(format!(
+                    "Exported {} of {total} section(s) to {folder_name}. {first}",
+                    total - errors.len()
+                )),
+            };
+            let _ = ui_tx.send(UiEvent::ExportFinished { result });
+            ctx.request_repaint();
+        });
+    }
+
+    /// Selection at the copy scale, refused above the copy pixel limit.
+    pub fn copy_target(&self) -> Result<ExportTarget, String> {
+        if !self.has_selection() {
+            return Err("Nothing is selected".to_string());
+        }
+        let target = ExportTarget::content(
+            self.selected_shapes(),
+            COPY_PADDING,
+            self.export_settings.copy_scale,
+        )?;
+        check_copy_limits(target.pixel_size())?;
+        Ok(target)
+    }
+
+    /// Render the selection through the export path at the copy scale. The
+    /// pixels come back as `UiEvent::CopyImageRendered` and are put on the
+    /// clipboard on the UI thread.
+    pub fn copy_selection_as_png(&mut self, ctx: &egui::Context) {
+        if !self.has_selection() {
+            self.notification = Some(("Nothing is selected".to_string(), Instant::now()));
+            return;
+        }
+        let target = match self.copy_target() {
+            Ok(target) => target,
+            Err(e) => {
+                self.notification = Some((format!("Copy as PNG failed: {}", e), Instant::now()));
+                return;
+            }
+        };
+
+        let bg = if self.export_settings.copy_transparent {
+            egui::Color32::TRANSPARENT
+        } else {
+            self.background_color
+        };
+        let ui_tx = self.ui_event_tx.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let pixels = render_rgba(&target, bg, &mut |_, _| {});
+            let _ = ui_tx.send(UiEvent::CopyImageRendered {
+                size: target.pixel_size(),
+                pixels,
+            });
+            ctx.request_repaint();
+        });
+    }
+
+    /// Put rendered Copy as PNG pixels on the system clipboard.
+    pub fn set_clipboard_image(
+        &mut self,
+        [width, height]: [u32; 2],
+        pixels: Result<Vec<u8>, String>,
+    ) -> Result<(), String> {
+        let pixels = pixels?;
+        self.system_clipboard()?
+            .set_image(arboard::ImageData {
+                width: width as usize,
+                height: height as usize,
+                bytes: pixels.into(),
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn export_file_dialog(&mut self, ctx: &egui::Context) {
+        if self.export_in_flight {
+            return;
+        }
+        let format = self.export_settings.format;
+        let target = self.export_target(ctx).and_then(|target| {
+            if !format.is_vector() {
+                check_pixel_limits(format, target.pixel_size())?;
+            }
+            Ok(target)
+        });
+        let target = match target {
+            Ok(target) => target,
+            Err(e) => {
+                self.notification = Some((format!("Canvas export failed: {}", e), Instant::now()));
+                return;
+            }
+        };
+        let ext = format.extension();
+
+        let default_name = if let Some(file_path) = &self.current_file_path {
+            file_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(|stem| format!("{}.{}", stem, ext))
+                .unwrap_or_else(|| format!("Untitled.{}", ext))
+        } else {
+            format!("Untitled.{}", ext)
+        };
+
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.filter_name(), &[ext])
+            .set_file_name(&default_name)
+            .save_file()
+        {
+            let settings = self.export_settings.clone();
+            let bg = self.background_color;
+            let ui_tx = self.ui_event_tx.clone();
+            let ctx = ctx.clone();
+            self.export_in_flight = true;
+            self.export_progress = None;
+            std::thread::spawn(move || {
+                let progress_tx = ui_tx.clone();
+                let progress_ctx = ctx.clone();
+                let result = write_target(&target, &settings, bg, &path, &mut |done, total| {
+                    let _ = progress_tx.send(UiEvent::ExportProgress { done, total });
+                    progress_ctx.request_repaint();
+                });
+                let _ = ui_tx.send(UiEvent::ExportFinished {
+                    result: result
+                        .map(|()| "Canvas exported successfully".to_string())
+                        .map_err(|e| format!("Canvas export failed: {}", e)),
+                });
+                ctx.request_repaint();
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect_shape(id: usize, x: f32) -> Shape {
+        let rect = egui::Rect::from_min_size(egui::pos2(x, 0.0), egui::vec2(10.0, 10.0));
+        Shape::new_rect(id, rect, egui::Color32::WHITE, 2.0, true)
+    }
+
+    #[test]
+    fn test_section_file_names() {
+        let titles = vec![
+            Some("Moodboard A".to_string()),
+            None,
+            Some("a/b: c?".to_string()),
+            Some("moodboard a".to_string()),
+            Some("  ".to_string()),
+        ];
+        assert_eq!(
+            section_file_names(&titles, "png"),
+            vec![
+                "Moodboard A.png",
+                "Section 2.png",
+                "a_b_ c_.png",
+                "moodboard a (2).png",
+                "Section 5.png",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_target_scopes() {
+        let ctx = egui::Context::default();
+        let mut app = App::default();
+        app.canvas.shapes = vec![
+            rect_shape(0, 0.0),
+            rect_shape(1, 100.0),
+            Shape::new_section(
+                2,
+                egui::Rect::from_min_size(egui::pos2(90.0, -10.0), egui::vec2(40.0, 40.0)),
+                egui::Color32::WHITE,
+            ),
+        ];
+        app.export_settings.padding = 5.0;
+        app.export_settings.scale = 2.0;
+
+        let board = app.export_target(&ctx).unwrap();
+        assert_eq!(board.region.min.x, -5.0);
+        assert_eq!(board.pages.len(), 1);
+        assert_eq!(board.pixel_size(), [2 * 140, 2 * 50]);
+
+        app.export_scope = ExportScope::Selection;
+        assert!(app.export_target(&ctx).is_err());
+        app.selected_shape_indices.insert(0);
+        let selection = app.export_target(&ctx).unwrap();
+        assert_eq!(selection.shapes.len(), 1);
+        assert_eq!(selection.region.max.x, 15.0);
+
+        app.export_scope = ExportScope::Section(2);
+        let section = app.export_target(&ctx).unwrap();
+        assert_eq!(section.shapes.len(), 2);
+        assert_eq!(section.pixel_size(), [80, 80]);
+
+        app.export_scope = ExportScope::Section(7);
+        assert!(app.export_target(&ctx).is_err());
+    }
+}
@@ -1,88 +1,117 @@
 use crate::app::App;
 use crate::canvas::Canvas;
-use crate::export::export_canvas_to_image;
 use crate::image_utils::{compress_and_scale, fit_display_size, process_file_to_images};
 use crate::markdown::{looks_like_markdown, strip_markdown};
 use crate::shapes::{ShapeData, Tool};
-use crate::state::CanvasState;
+use crate::state::backup::save_with_backups;
+use crate::state::container::{load_board, read_board, read_board_salvaged};
+use crate::state::error::LoadError;
+use crate::state::{CanvasState, FORMAT_VERSION};
 use eframe::egui;
 use std::path::Path;
 use std::time::Instant;
 
 impl App {
     pub fn open_kugel_file(&mut self, path: &Path, ctx: &egui::Context) -> bool {
-        if let Ok(json) = std::fs::read_to_string(path) {
-            if let Ok(state) = serde_json::from_str::<CanvasState>(&json) {
-                self.canvas.shapes = state.shapes;
-                self.canvas.next_id = state.next_id;
-                self.background_color = egui::Color32::from_rgba_unmultiplied(
-                    state.background_color[0],
-                    state.background_color[1],
-                    state.background_color[2],
-                    state.background_color[3],
-                );
-                self.zoom = state.zoom;
-                self.pan_offset = egui::vec2(state.pan_offset[0], state.pan_offset[1]);
-                self.dark_mode = state.dark_mode;
-                self.canvas.load_textures(ctx);
-                self.clear_selection();
-                self.editing_text_index = None;
-                self.generate_missing_link_previews(ctx);
-                self.current_file_path = Some(path.to_path_buf());
-                self.is_dirty = false;
-                self.notification = Some((
-                    format!(
-                        "Opened board: {}",
-                        path.file_name().unwrap_or_default().to_string_lossy()
-                    ),
-                    Instant::now(),
-                ));
-                return true;
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                self.report_open_error(&name, &LoadError::Io(e));
+                return false;
             }
-        }
-        false
-    }
-
-    pub fn save(&mut self) -> bool {
-        if let Some(path) = self.current_file_path.clone() {
-            self.save_to_path(&path)
-        } else {
-            self.save_file_dialog()
-        }
-    }
+        };
 
-    pub fn new_board(&mut self) {
-        let empty_unsaved = self.canvas.shapes.is_empty() && self.current_file_path.is_none();
-        if self.is_dirty && !empty_unsaved {
-            let confirm = rfd::MessageDialog::new()
-                .set_title("Unsaved Changes")
-                .set_description("Do you want to save the current board before creating a new one?")
-                .set_buttons(rfd::MessageButtons::YesNoCancel)
-                .show();
-            match confirm {
-                rfd::MessageDialogResult::Yes => {
-                    if !self.save() {
-                        return;
+        let (state, dropped) = match read_board(&bytes) {
+            Ok(state) => (state, Vec::new()),
+            Err(e) if e.is_shape_error() => {
+                let salvage = rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title("Board Partly Damaged")
+                    .set_description(format!(
+                        "{} could not be opened completely: {}.\n\nOpen it anyway and leave out the shapes that cannot be read?",
+                        name, e
+                    ))
+                    .set_buttons(rfd::MessageButtons::YesNo)
+                    .show();
+                if salvage != rfd::MessageDialogResult::Yes {
+                    self.notification =
+                        Some((format!("Opening board failed: {}", e), Instant::now()));
+                    return false;
+                }
+                match read_board_salvaged(&bytes) {
+                    Ok(salvaged) => salvaged,
+                    Err(e) => {
+                        self.report_open_error(&name, &e);
+                        return false;
                     }
                 }
-                rfd::MessageDialogResult::No => {}
-                _ => return,
             }
+            Err(e) => {
+                self.report_open_error(&name, &e);
+                return false;
+            }
+        };
+
+        self.remember_board_view();
+        self.apply_board_state(state, ctx);
+        self.current_file_path = Some(path.to_path_buf());
+        self.record_file_mtime();
+        self.apply_remembered_view(path);
+        self.note_recent_board(false, ctx);
+        // A salvaged board differs from the file until it is saved again.
+        self.is_dirty = !dropped.is_empty();
+        if dropped.is_empty() {
+            self.notification = Some((format!("Opened board: {}", name), Instant::now()));
+        } else {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Shapes Left Out")
+                .set_description(describe_dropped(&name, &dropped))
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+            self.notification = Some((
+                format!("Opened board: {} ({} shape(s) left out)", name, dropped.len()),
+                Instant::now(),
+            ));
         }
+        true
+    }
 
-        self.canvas = Canvas::default();
-        self.current_file_path = None;
-        self.is_dirty = false;
+    fn report_open_error(&mut self, name: &str, err: &LoadError) {
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Opening Board Failed")
+            .set_description(format!("{} could not be opened: {}", name, err))
+            .set_buttons(rfd::MessageButtons::Ok)
+            .show();
+        self.notification = Some((format!("Opening board failed: {}", err), Instant::now()));
+    }
+
+    /// Replace the current board contents and view with a loaded state.
+    pub fn apply_board_state(&mut self, state: CanvasState, ctx: &egui::Context) {
+        self.canvas.shapes = state.shapes;
+        self.canvas.next_id = state.next_id;
+        self.background_color = egui::Color32::from_rgba_unmultiplied(
+            state.background_color[0],
+            state.background_color[1],
+            state.background_color[2],
+            state.background_color[3],
+        );
+        self.zoom = state.zoom;
+        self.pan_offset = egui::vec2(state.pan_offset[0], state.pan_offset[1]);
+        self.dark_mode = state.dark_mode;
+        self.canvas.load_textures(ctx);
         self.clear_selection();
+        self.entered_group = None;
         self.editing_text_index = None;
-        self.zoom = 1.0;
-        self.pan_offset = egui::Vec2::ZERO;
-        self.notification = Some(("New board created".to_string(), Instant::now()));
+        self.generate_missing_link_previews(ctx);
     }
 
-    pub fn save_to_path(&mut self, path: &Path) -> bool {
-        let state = CanvasState {
-            version: "1.0".to_string(),
+    /// Snapshot of the current board in its on-disk representation.
+    pub fn board_state(&self) -> CanvasState {
+        CanvasState {
+            version: FORMAT_VERSION.to_string(),
             shapes: self.canvas.shapes.clone(),
             background_color: [
                 self.background_color.r(),
@@ -94,94 +123,185 @@ impl App {
             pan_offset: [self.pan_offset.x, self.pan_offset.y],
             next_id: self.canvas.next_id,
             dark_mode: self.dark_mode,
-        };
-        if let Ok(json) = serde_json::to_string_pretty(&state) {
-            if std::fs::write(path, json).is_ok() {
-                self.current_file_path = Some(path.to_path_buf());
-                self.is_dirty = false;
-                self.notification = Some((
-                    "Saved board state successfully".to_string(),
-                    Instant::now(),
-                ));
-                return true;
+        }
+    }
+
+    /// Merge all shapes of another board into this one around `target`,
+    /// leaving them selected.
+    pub fn insert_board(&mut self, path: &Path, target: egui::Pos2, ctx: &egui::Context) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let state = match load_board(path) {
+            Ok(state) => state,
+            Err(e) => {
+                self.notification =
+                    Some((format!("Inserting board failed: {}", e), Instant::now()));
+                return;
             }
+        };
+        if state.shapes.is_empty() {
+            self.notification = Some((format!("{} has no shapes to insert", name), Instant::now()));
+            return;
         }
+
+        let inserted = self.canvas.insert_shapes(state.shapes, target, ctx);
+        let count = inserted.len();
+        self.clear_selection();
+        self.selected_shape_indices.extend(inserted.clone());
+        self.primary_selected = inserted.last();
+        self.editing_text_index = None;
+        self.tool = Tool::Select;
+        self.is_dirty = true;
+        self.generate_missing_link_previews(ctx);
         self.notification = Some((
-            "Saving board state failed".to_string(),
+            format!("Inserted {} shape(s) from {}", count, name),
             Instant::now(),
         ));
-        false
     }
 
-    pub fn save_file_dialog(&mut self) -> bool {
+    pub fn insert_board_dialog(&mut self, ctx: &egui::Context) {
         if let Some(path) = rfd::FileDialog::new()
             .add_filter("Kugel Mood Board", &["kugel"])
-            .save_file()
+            .pick_file()
         {
-            return self.save_to_path(&path);
+            let target = self.screen_to_canvas(ctx.screen_rect().center());
+            self.insert_board(&path, target, ctx);
         }
-        false
     }
 
-    pub fn open_file_dialog(&mut self, ctx: &egui::Context) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("Kugel Mood Board", &["kugel"])
-            .pick_file()
-        {
-            if !self.open_kugel_file(&path, ctx) {
+    pub fn save(&mut self, ctx: &egui::Context) -> bool {
+        if let Some(path) = self.current_file_path.clone() {
+            self.save_to_path(&path, ctx)
+        } else {
+            self.save_file_dialog(ctx)
+        }
+    }
+
+    /// Ask whether to save unsaved changes before `action` replaces the board.
+    /// Returns false when the user cancelled or saving failed.
+    pub fn confirm_unsaved_changes(&mut self, action: &str, ctx: &egui::Context) -> bool {
+        let empty_unsaved = self.canvas.shapes.is_empty() && self.current_file_path.is_none();
+        if !self.is_dirty || empty_unsaved {
+            return true;
+        }
+        let confirm = rfd::MessageDialog::new()
+            .set_title("Unsaved Changes")
+            .set_description(format!(
+                "Do you want to save the current board before {}?",
+                action
+            ))
+            .set_buttons(rfd::MessageButtons::YesNoCancel)
+            .show();
+        match confirm {
+            rfd::MessageDialogResult::Yes => self.save(ctx),
+            rfd::MessageDialogResult::No => true,
+            _ => false,
+        }
+    }
+
+    pub fn new_board(&mut self, ctx: &egui::Context) {
+        if !self.confirm_unsaved_changes("creating a new one", ctx) {
+            return;
+        }
+
+        self.remember_board_view();
+        self.discard_recovery();
+        self.canvas = Canvas::default();
+        self.current_file_path = None;
+        self.file_mtime = None;
+        self.is_dirty = false;
+        self.clear_selection();
+        self.entered_group = None;
+        self.editing_text_index = None;
+        self.zoom = 1.0;
+        self.pan_offset = egui::Vec2::ZERO;
+        self.notification = Some(("New board created".to_string(), Instant::now()));
+    }
+
+    pub fn save_to_path(&mut self, path: &Path, ctx: &egui::Context) -> bool {
+        if self.current_file_path.as_deref() == Some(path) && self.file_changed_on_disk() {
+            let overwrite = rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Board Changed on Disk")
+                .set_description(format!(
+                    "{} was changed by another program since you opened it. Overwrite it with your version?",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if overwrite != rfd::MessageDialogResult::Yes {
+                self.show_file_changed_dialog = true;
+                return false;
+            }
+        }
+
+        match save_with_backups(path, self.board_state()) {
+            Ok(backup_error) => {
+                self.canvas.assets.prune();
+                // Drop the autosave under both the old key (e.g. "untitled"
+                // on Save As) and the new one.
+                self.discard_recovery();
+                self.current_file_path = Some(path.to_path_buf());
+                self.record_file_mtime();
+                self.discard_recovery();
+                self.note_recent_board(true, ctx);
+                self.is_dirty = false;
+                let message = match backup_error {
+                    None => "Saved board state successfully".to_string(),
+                    Some(e) => format!("Saved board state, but the backup failed: {}", e),
+                };
+                self.notification = Some((message, Instant::now()));
+                true
+            }
+            Err(e) => {
                 self.notification = Some((
-                    "Opening board state failed: invalid file".to_string(),
+                    format!("Saving board state failed: {}", e),
                     Instant::now(),
                 ));
+                false
             }
         }
     }
 
-    pub fn export_file_dialog(&mut self) {
-        let filter_name = if self.export_jpeg {
-            "JPEG Image"
-        } else {
-            "PNG Image"
-        };
-        let ext = if self.export_jpeg { "jpg" } else { "png" };
-
-        let default_name = if let Some(file_path) = &self.current_file_path {
-            file_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|stem| format!("{}.{}", stem, ext))
-                .unwrap_or_else(|| format!("Untitled.{}", ext))
-        } else {
-            format!("Untitled.{}", ext)
-        };
+    /// Load a `.kugel.bak` rotation of the current board. The board file
+    /// itself is only overwritten once the user saves.
+    pub fn restore_backup(&mut self, backup: &Path, ctx: &egui::Context) {
+        if !self.confirm_unsaved_changes("restoring a backup", ctx) {
+            return;
+        }
+        match load_board(backup) {
+            Ok(state) => {
+                self.apply_board_state(state, ctx);
+                self.is_dirty = true;
+                self.notification = Some((
+                    "Restored backup. Save to keep it.".to_string(),
+                    Instant::now(),
+                ));
+            }
+            Err(e) => {
+                self.notification = Some((
+                    format!("Restoring backup failed: {}", e),
+                    Instant::now(),
+                ));
+            }
+        }
+    }
 
+    pub fn save_file_dialog(&mut self, ctx: &egui::Context) -> bool {
         if let Some(path) = rfd::FileDialog::new()
-            .add_filter(filter_name, &[ext])
-            .set_file_name(&default_name)
+            .add_filter("Kugel Mood Board", &["kugel"])
             .save_file()
         {
-            match export_canvas_to_image(
-                &self.canvas.shapes,
-                self.background_color,
-                self.export_scale,
-                &path,
-                self.export_jpeg,
-                self.export_quality,
-            ) {
-                Ok(_) => {
-                    self.show_export_dialog = false;
-                    self.notification = Some((
-                        "Canvas exported successfully".to_string(),
-                        Instant::now(),
-                    ));
-                }
-                Err(e) => {
-                    self.notification = Some((
-                        format!("Canvas export failed: {}", e),
-                        Instant::now(),
-                    ));
-                }
-            }
+            return self.save_to_path(&path, ctx);
+        }
+        false
+    }
+
+    pub fn open_file_dialog(&mut self, ctx: &egui::Context) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Kugel Mood Board", &["kugel"])
+            .pick_file()
+        {
+            self.open_kugel_file(&path, ctx);
         }
     }
 
@@ -384,6 +504,23 @@ impl App {
     }
 }
 
+/// Dialog text listing the shapes a salvaged board was opened without.
+fn describe_dropped(name: &str, dropped: &[LoadError]) -> String {
+    const MAX_LISTED: usize = 10;
+    let mut text = format!(
+        "{} was opened without {} shape(s) that could not be read:\n",
+        name,
+        dropped.len()
+    );
+    for err in dropped.iter().take(MAX_LISTED) {
+        text.push_str(&format!("\n- {}", err));
+    }
+    if dropped.len() > MAX_LISTED {
+        text.push_str(&format!("\n- ...and {} more", dropped.len() - MAX_LISTED));
+    }
+    text
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -405,4 +542,19 @@ mod tests {
         assert_eq!(app.primary_selected, Some(0));
         assert_eq!(app.tool, Tool::Select);
     }
+
+    #[test]
+    fn test_describe_dropped_caps_list() {
+        let dropped: Vec<LoadError> = (0..12)
+            .map(|index| LoadError::UnknownShape {
+                index,
+                variant: "Blob".to_string(),
+            })
+            .collect();
+        let text = describe_dropped("board.kugel", &dropped);
+        assert!(text.starts_with("board.kugel was opened without 12 shape(s)"));
+        assert!(text.contains("- shape 1 has unknown type \"Blob\""));
+        assert!(!text.contains("shape 11 "));
+        assert!(text.ends_with("...and 2 more"));
+    }
 }
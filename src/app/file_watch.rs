@@ -0,0 +1,167 @@
+use crate::app::App;
+use crate::assets::content_hash;
+use crate::shapes::{Shape, ShapeData};
+use crate::state::container::load_board;
+use eframe::egui;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the open board's modification time is compared with the disk.
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn disk_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Content of a shape without its id or decoded texture, used to tell which
+/// shapes of the version on disk already exist locally.
+fn shape_fingerprint(shape: &Shape) -> String {
+    let mut data = shape.data.clone();
+    if let ShapeData::Image {
+        bytes,
+        asset,
+        texture,
+        ..
+    } = &mut data
+    {
+        if asset.is_none() {
+            *asset = Some(content_hash(bytes));
+        }
+        *bytes = Arc::from(Vec::new());
+        *texture = None;
+    }
+    serde_json::to_string(&data).unwrap_or_default()
+}
+
+/// Shapes of `theirs` that have no identical counterpart in `ours`.
+pub fn shapes_missing_from(ours: &[Shape], theirs: Vec<Shape>) -> Vec<Shape> {
+    let known: HashSet<String> = ours.iter().map(shape_fingerprint).collect();
+    theirs
+        .into_iter()
+        .filter(|shape| !known.contains(&shape_fingerprint(shape)))
+        .collect()
+}
+
+impl App {
+    /// Remember the modification time of the board as it is on disk now, so
+    /// later changes by other programs can be detected.
+    pub fn record_file_mtime(&mut self) {
+        self.file_mtime = self.current_file_path.as_deref().and_then(disk_mtime);
+    }
+
+    /// True when the open board was modified on disk since it was opened or saved.
+    pub fn file_changed_on_disk(&self) -> bool {
+        let Some(path) = &self.current_file_path else {
+            return false;
+        };
+        match (self.file_mtime, disk_mtime(path)) {
+            (Some(known), Some(disk)) => known != disk,
+            _ => false,
+        }
+    }
+
+    /// Poll the open board for external modifications and raise the
+    /// reload prompt when one is found.
+    pub fn check_external_changes(&mut self, ctx: &egui::Context) {
+        if self.current_file_path.is_none() || self.show_file_changed_dialog {
+            return;
+        }
+        ctx.request_repaint_after(DISK_CHECK_INTERVAL);
+        if self.last_disk_check.elapsed() < DISK_CHECK_INTERVAL {
+            return;
+        }
+        self.last_disk_check = Instant::now();
+        if self.file_changed_on_disk() {
+            self.show_file_changed_dialog = true;
+        }
+    }
+
+    /// Replace the board with the version on disk, dropping local changes.
+    pub fn reload_from_disk(&mut self, ctx: &egui::Context) {
+        self.show_file_changed_dialog = false;
+        if let Some(path) = self.current_file_path.clone() {
+            self.discard_recovery();
+            self.open_kugel_file(&path, ctx);
+        }
+    }
+
+    /// Keep the local board; the next save overwrites the version on disk.
+    pub fn keep_local_version(&mut self) {
+        self.show_file_changed_dialog = false;
+        self.record_file_mtime();
+        self.is_dirty = true;
+    }
+
+    /// Add the shapes that only exist in the version on disk to the local
+    /// board as one undo step, leaving them selected.
+    pub fn merge_from_disk(&mut self, ctx: &egui::Context) {
+        self.show_file_changed_dialog = false;
+        let Some(path) = self.current_file_path.clone() else {
+            return;
+        };
+        let theirs = match load_board(&path) {
+            Ok(state) => state.shapes,
+            Err(e) => {
+                self.notification = Some((format!("Merging failed: {}", e), Instant::now()));
+                return;
+            }
+        };
+        self.record_file_mtime();
+
+        let missing = shapes_missing_from(&self.canvas.shapes, theirs);
+        let added = self.canvas.append_shapes(missing, ctx);
+        let count = added.len();
+        self.clear_selection();
+        self.selected_shape_indices.extend(added.clone());
+        self.primary_selected = added.last();
+        self.is_dirty = true;
+        self.notification = Some((
+            format!("Merged {} shape(s) from the version on disk", count),
+            Instant::now(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(id: usize, x: f32) -> Shape {
+        let rect = egui::Rect::from_min_size(egui::pos2(x, 0.0), egui::vec2(10.0, 10.0));
+        Shape::new_rect(id, rect, egui::Color32::WHITE, 2.0, false)
+    }
+
+    #[test]
+    fn test_shapes_missing_from_ignores_ids() {
+        let ours = vec![rect(0, 0.0), rect(1, 20.0)];
+        let theirs = vec![rect(5, 0.0), rect(1, 40.0)];
+        let missing = shapes_missing_from(&ours, theirs);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].data.get_bounds().min.x, 40.0);
+    }
+
+    #[test]
+    fn test_file_changed_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("board.kugel");
+        std::fs::write(&path, b"one").unwrap();
+
+        let mut app = App {
+            current_file_path: Some(path.clone()),
+            ..App::default()
+        };
+        app.record_file_mtime();
+        assert!(!app.file_changed_on_disk());
+
+        let later = SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(app.file_changed_on_disk());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::app::App;
+use std::time::Instant;
+
+impl App {
+    /// Group the selected shapes (Cmd/Ctrl + G). Shapes already grouped
+    /// move into the new group.
+    pub fn group_selection(&mut self) {
+        let len = self.canvas.shapes.len();
+        let indices: Vec<usize> = self
+            .selected_shape_indices
+            .iter()
+            .copied()
+            .filter(|&idx| idx < len)
+            .collect();
+        if indices.len() < 2 {
+            return;
+        }
+        self.canvas.push_history();
+        let group = self.canvas.next_id;
+        self.canvas.next_id += 1;
+        for &idx in &indices {
+            self.canvas.shapes[idx].group = Some(group);
+        }
+        self.entered_group = None;
+        self.is_dirty = true;
+        self.notification = Some((format!("Grouped {} shapes", indices.len()), Instant::now()));
+    }
+
+    /// Dissolve the groups of the selected shapes (Cmd/Ctrl + Shift + G).
+    pub fn ungroup_selection(&mut self) {
+        let grouped: Vec<usize> = self
+            .selected_shape_indices
+            .iter()
+            .copied()
+            .filter(|&idx| self.canvas.shapes.get(idx).is_some_and(|s| s.group.is_some()))
+            .collect();
+        if grouped.is_empty() {
+            return;
+        }
+        self.canvas.push_history();
+        for idx in grouped {
+            self.canvas.shapes[idx].group = None;
+        }
+        self.entered_group = None;
+        self.is_dirty = true;
+        self.notification = Some(("Ungrouped".to_string(), Instant::now()));
+    }
+
+    /// Indices selected together with the shape at `idx`: its whole group,
+    /// unless that group has been entered for editing single members.
+    pub fn group_members(&self, idx: usize) -> Vec<usize> {
+        match self.canvas.shapes.get(idx).and_then(|s| s.group) {
+            Some(group) if self.entered_group != Some(group) => self
+                .canvas
+                .shapes
+                .iter()
+                .enumerate()
+                .filter(|(_, shape)| shape.group == Some(group) && !shape.locked)
+                .map(|(i, _)| i)
+                .collect(),
+            _ => vec![idx],
+        }
+    }
+
+    /// Select a clicked shape along with the rest of its group. Clicking
+    /// anything outside the entered group leaves it.
+    pub fn select_with_group(&mut self, idx: usize) {
+        self.leave_group_unless_member(idx);
+        self.selected_shape_indices.clear();
+        self.selected_shape_indices.extend(self.group_members(idx));
+        self.primary_selected = Some(idx);
+    }
+
+    /// Shift + click: add or remove the clicked shape's whole group.
+    pub fn toggle_with_group(&mut self, idx: usize) {
+        let members = self.group_members(idx);
+        if self.selected_shape_indices.contains(&idx) {
+            for member in &members {
+                self.selected_shape_indices.remove(member);
+            }
+            if self.primary_selected.is_some_and(|p| members.contains(&p)) {
+                self.primary_selected = self.selected_shape_indices.iter().next().copied();
+            }
+        } else {
+            self.selected_shape_indices.extend(members);
+            self.primary_selected = Some(idx);
+        }
+    }
+
+    /// Complete a marquee selection to whole groups.
+    pub fn expand_selection_to_groups(&mut self) {
+        let selected: Vec<usize> = self.selected_shape_indices.iter().copied().collect();
+        for idx in selected {
+            self.selected_shape_indices.extend(self.group_members(idx));
+        }
+    }
+
+    /// Double click on a grouped shape: open its group and select just that
+    /// member. Returns false when the shape is not in a closed group.
+    pub fn enter_group(&mut self, idx: usize) -> bool {
+        let Some(group) = self.canvas.shapes.get(idx).and_then(|s| s.group) else {
+            return false;
+        };
+        if self.entered_group == Some(group) {
+            return false;
+        }
+        self.entered_group = Some(group);
+        self.select_single(idx);
+        true
+    }
+
+    fn leave_group_unless_member(&mut self, idx: usize) {
+        let group = self.canvas.shapes.get(idx).and_then(|s| s.group);
+        if self.entered_group.is_some() && self.entered_group != group {
+            self.entered_group = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui;
+
+    fn app_with_three_texts() -> App {
+        let mut app = App::default();
+        for (i, name) in ["A", "B", "C"].iter().enumerate() {
+            app.canvas
+                .add_text(egui::pos2(i as f32 * 100.0, 0.0), name.to_string(), egui::Color32::WHITE);
+        }
+        app
+    }
+
+    #[test]
+    fn test_grouped_shapes_select_together_until_entered() {
+        let mut app = app_with_three_texts();
+        app.selected_shape_indices.extend([0, 1]);
+        app.group_selection();
+        app.clear_selection();
+
+        app.select_with_group(1);
+        assert_eq!(app.selected_shape_indices.len(), 2);
+        assert_eq!(app.primary_selected, Some(1));
+
+        assert!(app.enter_group(1));
+        assert_eq!(app.selected_shape_indices.len(), 1);
+        app.select_with_group(0);
+        assert_eq!(app.selected_shape_indices.len(), 1);
+
+        // Clicking a shape outside the group closes it again
+        app.select_with_group(2);
+        assert_eq!(app.entered_group, None);
+        app.select_with_group(0);
+        assert_eq!(app.selected_shape_indices.len(), 2);
+
+        app.ungroup_selection();
+        app.select_with_group(0);
+        assert_eq!(app.selected_shape_indices.len(), 1);
+    }
+
+    #[test]
+    fn test_groups_survive_save_and_get_fresh_ids_when_copied() {
+        let mut app = app_with_three_texts();
+        app.selected_shape_indices.extend([0, 1]);
+        app.group_selection();
+        let group = app.canvas.shapes[0].group;
+        assert!(group.is_some());
+
+        let json = serde_json::to_string(&app.canvas.shapes).unwrap();
+        let loaded: Vec<crate::shapes::Shape> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded[1].group, group);
+        assert_eq!(loaded[2].group, None);
+
+        let ctx = egui::Context::default();
+        let range = app.canvas.append_shapes(loaded[..2].to_vec(), &ctx);
+        let copied = app.canvas.shapes[range.start].group;
+        assert!(copied.is_some() && copied != group);
+        assert_eq!(app.canvas.shapes[range.start + 1].group, copied);
+    }
+
+    #[test]
+    fn test_duplicate_gives_copies_their_own_group() {
+        let mut app = app_with_three_texts();
+        app.selected_shape_indices.extend([0, 1]);
+        app.group_selection();
+        let group = app.canvas.shapes[0].group;
+
+        let ctx = egui::Context::default();
+        app.duplicate_selection(egui::vec2(20.0, 20.0), &ctx);
+        assert_eq!(app.canvas.shapes.len(), 5);
+        let copy = &app.canvas.shapes[3];
+        assert!(copy.group.is_some() && copy.group != group);
+        assert_eq!(app.canvas.shapes[4].group, copy.group);
+        let moved = copy.get_bounds().min - app.canvas.shapes[0].get_bounds().min;
+        assert_eq!(moved, egui::vec2(20.0, 20.0));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::app::App;
+use eframe::egui;
+use std::time::Instant;
+
+/// Radius in screen pixels of the lock badge shown on a hovered locked shape.
+const LOCK_BADGE_RADIUS: f32 = 10.0;
+
+impl App {
+    /// Lock the selected shapes (Cmd/Ctrl + Shift + L) and drop them from the
+    /// selection.
+    pub fn lock_selection(&mut self) {
+        let len = self.canvas.shapes.len();
+        let indices: Vec<usize> = self
+            .selected_shape_indices
+            .iter()
+            .copied()
+            .filter(|&idx| idx < len)
+            .collect();
+        if indices.is_empty() {
+            return;
+        }
+        self.canvas.push_history();
+        for &idx in &indices {
+            self.canvas.shapes[idx].locked = true;
+        }
+        self.clear_selection();
+        self.is_dirty = true;
+        self.notification = Some((format!("Locked {} shape(s)", indices.len()), Instant::now()));
+    }
+
+    pub fn unlock_all(&mut self) {
+        let count = self.canvas.shapes.iter().filter(|s| s.locked).count();
+        if count == 0 {
+            self.notification = Some(("Nothing is locked".to_string(), Instant::now()));
+            return;
+        }
+        self.canvas.push_history();
+        for shape in &mut self.canvas.shapes {
+            shape.locked = false;
+        }
+        self.is_dirty = true;
+        self.notification = Some((format!("Unlocked {} shape(s)", count), Instant::now()));
+    }
+
+    /// Unlock a single shape and select it.
+    pub fn unlock_shape(&mut self, idx: usize) {
+        if !self.canvas.shapes.get(idx).is_some_and(|s| s.locked) {
+            return;
+        }
+        self.canvas.push_history();
+        self.canvas.shapes[idx].locked = false;
+        self.select_single(idx);
+        self.is_dirty = true;
+        self.notification = Some(("Unlocked shape".to_string(), Instant::now()));
+    }
+
+    /// Topmost locked shape under `canvas_pos` that no selectable shape covers.
+    pub fn locked_shape_at(&self, canvas_pos: egui::Pos2) -> Option<usize> {
+        if self.hit_test(canvas_pos).is_some() {
+            return None;
+        }
+        self.canvas
+            .shapes
+            .iter()
+            .rposition(|shape| shape.locked && shape.contains_point(canvas_pos, 5.0))
+    }
+
+    /// Screen position of the lock badge of a locked shape: its top right corner.
+    pub fn lock_badge_pos(&self, idx: usize) -> Option<egui::Pos2> {
+        let shape = self.canvas.shapes.get(idx).filter(|s| s.locked)?;
+        Some(self.canvas_to_screen(shape.get_bounds().right_top()))
+    }
+
+    /// Locked shape whose badge is under `mouse_pos` (screen coordinates).
+    pub fn lock_badge_under_mouse(&self, mouse_pos: egui::Pos2) -> Option<usize> {
+        (0..self.canvas.shapes.len()).rev().find(|&idx| {
+            self.lock_badge_pos(idx)
+                .is_some_and(|badge| mouse_pos.distance(badge) <= LOCK_BADGE_RADIUS)
+        })
+    }
+
+    /// Locked shape whose badge should show for the pointer at `mouse_pos`.
+    pub fn hovered_locked_shape(&self, mouse_pos: egui::Pos2) -> Option<usize> {
+        self.lock_badge_under_mouse(mouse_pos)
+            .or_else(|| self.locked_shape_at(self.screen_to_canvas(mouse_pos)))
+    }
+}
+
+/// Small padlock badge centered on `center`.
+pub fn draw_lock_badge(painter: &egui::Painter, center: egui::Pos2, dark: bool) {
+    let (bg, fg) = if dark {
+        (egui::Color32::from_rgb(31, 41, 55), egui::Color32::WHITE)
+    } else {
+        (egui::Color32::WHITE, egui::Color32::from_rgb(31, 41, 55))
+    };
+    painter.circle(
+        center,
+        LOCK_BADGE_RADIUS,
+        bg,
+        egui::Stroke::new(1.0, egui::Color32::from_rgb(99, 102, 241)),
+    );
+    let body = egui::Rect::from_center_size(center + egui::vec2(0.0, 2.0), egui::vec2(9.0, 7.0));
+    painter.rect_filled(body, 1.0, fg);
+    let shackle: Vec<egui::Pos2> = (0..=8)
+        .map(|i| {
+            let angle = std::f32::consts::PI * (1.0 + i as f32 / 8.0);
+            body.center_top() + egui::vec2(angle.cos(), angle.sin()) * 3.0
+        })
+        .collect();
+    painter.add(egui::Shape::line(shackle, egui::Stroke::new(1.5, fg)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_shapes_are_skipped_until_unlocked() {
+        let mut app = App::default();
+        app.canvas.add_text(egui::pos2(0.0, 0.0), "Background".into(), egui::Color32::WHITE);
+        app.canvas.add_text(egui::pos2(300.0, 0.0), "Free".into(), egui::Color32::WHITE);
+        let inside = app.canvas.shapes[0].get_bounds().center();
+        app.select_single(0);
+
+        app.lock_selection();
+        assert!(!app.has_selection());
+        assert_eq!(app.hit_test(inside), None);
+        assert_eq!(app.locked_shape_at(inside), Some(0));
+        app.select_all();
+        assert_eq!(app.selected_shape_indices.len(), 1);
+
+        app.unlock_all();
+        assert_eq!(app.hit_test(inside), Some(0));
+        assert!(!app.canvas.shapes[0].locked);
+    }
+}
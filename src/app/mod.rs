@@ -1,16 +1,28 @@
+pub mod arrange;
+pub mod clipboard;
+pub mod export;
 pub mod font;
+pub mod group;
 pub mod file_io;
+pub mod file_watch;
 pub mod link_preview;
+pub mod lock;
+pub mod recent;
+pub mod recovery;
 pub mod selection;
 pub mod ui;
 
 use crate::canvas::Canvas;
+use crate::export::ExportSettings;
 use crate::icons::Icons;
 use crate::shapes::{Shape, Tool};
 use crate::updater::{spawn_update_check, UiEvent, UpdateState};
 use eframe::egui;
+use export::ExportScope;
 use font::setup_custom_fonts;
-use std::collections::HashSet;
+use recent::RecentBoard;
+use selection::RotationDrag;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::mpsc;
 use std::time::Instant;
@@ -34,12 +46,21 @@ pub struct App {
     pub drag_start_pos: egui::Pos2,
     pub snap_correction: egui::Vec2,
     pub marquee_start: Option<egui::Pos2>,
+    pub rotation_drag: Option<RotationDrag>,
+    /// Group opened by double click, whose members are selected one by one.
+    pub entered_group: Option<usize>,
 
     // Copy / Paste buffer
-    pub copied_shape: Option<Shape>,
+    /// Copied shapes, used only while the system clipboard can be neither
+    /// written nor read.
+    pub copied_shapes: Vec<Shape>,
+    /// System clipboard, opened on first use and kept for the whole session.
+    pub clipboard: Option<arboard::Clipboard>,
 
     // Recoloring selection drag state
     pub recoloring_selection: bool,
+    /// Opacity slider drag in progress (one undo step per drag).
+    pub changing_opacity: bool,
 
     // Text editing state
     pub editing_text_index: Option<usize>,
@@ -48,9 +69,11 @@ pub struct App {
 
     // Export overlay
     pub show_export_dialog: bool,
-    pub export_scale: f32,
-    pub export_jpeg: bool,
-    pub export_quality: i32,
+    pub export_settings: ExportSettings,
+    pub export_scope: ExportScope,
+    pub export_in_flight: bool,
+    /// Tiles done and total of the running single-file export.
+    pub export_progress: Option<(usize, usize)>,
 
     // Notifications
     pub notification: Option<(String, Instant)>,
@@ -64,6 +87,17 @@ pub struct App {
     pub current_file_path: Option<PathBuf>,
     pub is_dirty: bool,
     pub close_confirmed: bool,
+    pub file_mtime: Option<std::time::SystemTime>,
+    pub last_disk_check: Instant,
+    pub show_file_changed_dialog: bool,
+    pub last_autosave: Instant,
+    pub autosave_in_flight: bool,
+
+    // Recent boards
+    pub recent_boards: Vec<RecentBoard>,
+    pub recent_thumbnails: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    pub restore_board_view: bool,
+    pub show_recent_dialog: bool,
 
     // UI state
     pub top_panel_collapsed: bool,
@@ -96,23 +130,37 @@ impl Default for App {
             is_dragging_shape: false,
             drag_start_pos: egui::Pos2::ZERO,
             snap_correction: egui::Vec2::ZERO,
-            copied_shape: None,
+            copied_shapes: Vec::new(),
+            clipboard: None,
             recoloring_selection: false,
+            changing_opacity: false,
             editing_text_index: None,
             editing_text_buffer: String::new(),
             request_text_focus: false,
             show_export_dialog: false,
-            export_scale: 2.0,
-            export_jpeg: false,
-            export_quality: 90,
+            export_settings: ExportSettings::default(),
+            export_scope: ExportScope::Board,
+            export_in_flight: false,
+            export_progress: None,
             notification: None,
             marquee_start: None,
+            rotation_drag: None,
+            entered_group: None,
             dark_mode: true,
             style_applied: false,
             last_system_theme: None,
             current_file_path: None,
             is_dirty: false,
             close_confirmed: false,
+            file_mtime: None,
+            last_disk_check: Instant::now(),
+            show_file_changed_dialog: false,
+            last_autosave: Instant::now(),
+            autosave_in_flight: false,
+            recent_boards: Vec::new(),
+            recent_thumbnails: HashMap::new(),
+            restore_board_view: false,
+            show_recent_dialog: false,
             top_panel_collapsed: false,
             icons: None,
             update_state: UpdateState::Idle,
@@ -137,10 +185,26 @@ impl App {
             .and_then(|s| eframe::get_value(s, "top_panel_collapsed"))
             .unwrap_or(false);
 
+        let recent_boards = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "recent_boards"))
+            .unwrap_or_default();
+        let restore_board_view = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "restore_board_view"))
+            .unwrap_or(false);
+        let export_settings = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, "export_settings"))
+            .unwrap_or_default();
+
         let mut app = Self {
             dark_mode,
             last_system_theme: system_theme,
             top_panel_collapsed,
+            recent_boards,
+            restore_board_view,
+            export_settings,
             icons: Some(Icons::new(&cc.egui_ctx)),
             ..Self::default()
         };
@@ -149,8 +213,8 @@ impl App {
             app.background_color = egui::Color32::from_gray(240);
         }
 
-        let mut opened = false;
-        if let Some(path_str) = std::env::args().nth(1) {
+        let mut opened = app.offer_recovery(&cc.egui_ctx);
+        if let Some(path_str) = std::env::args().nth(1).filter(|_| !opened) {
             let path = std::path::Path::new(&path_str);
             if path.exists() && path.is_file() {
                 opened = app.open_kugel_file(path, &cc.egui_ctx);
@@ -239,6 +303,38 @@ impl App {
                         }
                     }
                 }
+                UiEvent::AutosaveFinished { path, error } => {
+                    self.autosave_in_flight = false;
+                    if let Some(err) = error {
+                        self.notification =
+                            Some((format!("Autosave failed: {err}"), Instant::now()));
+                    } else if !self.is_dirty {
+                        // Saved while the autosave was being written.
+                        recovery::remove_recovery_file(&path);
+                    }
+                }
+                UiEvent::ThumbnailRendered(path) => {
+                    self.recent_thumbnails.remove(&path);
+                }
+                UiEvent::ExportProgress { done, total } => {
+                    self.export_progress = Some((done, total));
+                }
+                UiEvent::ExportFinished { result } => {
+                    self.export_in_flight = false;
+                    self.export_progress = None;
+                    if result.is_ok() {
+                        self.show_export_dialog = false;
+                    }
+                    let message = result.unwrap_or_else(|e| e);
+                    self.notification = Some((message, Instant::now()));
+                }
+                UiEvent::CopyImageRendered { size, pixels } => {
+                    let message = match self.set_clipboard_image(size, pixels) {
+                        Ok(()) => "Copied selection as PNG".to_string(),
+                        Err(e) => format!("Copy as PNG failed: {e}"),
+                    };
+                    self.notification = Some((message, Instant::now()));
+                }
             }
         }
     }
@@ -0,0 +1,201 @@
+use crate::app::recovery::board_key;
+use crate::app::App;
+use crate::export::render_thumbnail;
+use crate::updater::UiEvent;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const MAX_RECENT_BOARDS: usize = 10;
+
+/// Longer side of a recent-board thumbnail in pixels.
+const THUMBNAIL_SIZE: f32 = 256.0;
+
+/// Entry of the persisted recent-files list, including the view the board was
+/// last left in.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecentBoard {
+    pub path: PathBuf,
+    /// Seconds since the Unix epoch.
+    pub last_opened: u64,
+    pub zoom: f32,
+    pub pan_offset: [f32; 2],
+}
+
+impl RecentBoard {
+    pub fn last_opened_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.last_opened)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// `<eframe storage dir>/thumbnails/<board key>.png`
+pub fn thumbnail_path(board: &Path) -> Option<PathBuf> {
+    eframe::storage_dir("Kugel").map(|dir| {
+        dir.join("thumbnails")
+            .join(format!("{}.png", board_key(Some(board))))
+    })
+}
+
+/// Move `path` to the front of the list (adding it if needed) and drop the
+/// oldest entries beyond `MAX_RECENT_BOARDS`.
+pub fn touch_recent(
+    list: &mut Vec<RecentBoard>,
+    path: &Path,
+    now: u64,
+    zoom: f32,
+    pan_offset: [f32; 2],
+) {
+    list.retain(|entry| entry.path != path);
+    list.insert(
+        0,
+        RecentBoard {
+            path: path.to_path_buf(),
+            last_opened: now,
+            zoom,
+            pan_offset,
+        },
+    );
+    list.truncate(MAX_RECENT_BOARDS);
+}
+
+impl App {
+    /// Put the open board at the top of the recent list. After a save its
+    /// view is recorded and its thumbnail re-rendered in the background; after
+    /// opening, the remembered view is kept and only a missing thumbnail is
+    /// rendered.
+    pub fn note_recent_board(&mut self, saved: bool, ctx: &egui::Context) {
+        let Some(path) = self.current_file_path.clone() else {
+            return;
+        };
+        let remembered = self
+            .recent_boards
+            .iter()
+            .find(|e| e.path == path)
+            .filter(|_| !saved)
+            .map(|e| (e.zoom, e.pan_offset));
+        let (zoom, pan_offset) =
+            remembered.unwrap_or((self.zoom, [self.pan_offset.x, self.pan_offset.y]));
+        touch_recent(&mut self.recent_boards, &path, unix_now(), zoom, pan_offset);
+
+        let Some(thumb) = thumbnail_path(&path) else {
+            return;
+        };
+        if !saved && thumb.exists() {
+            return;
+        }
+        let shapes = self.canvas.shapes.clone();
+        let bg = self.background_color;
+        let ui_tx = self.ui_event_tx.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let Ok(png) = render_thumbnail(&shapes, bg, THUMBNAIL_SIZE) else {
+                return;
+            };
+            let written = thumb
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&thumb, png));
+            if written.is_ok() {
+                let _ = ui_tx.send(UiEvent::ThumbnailRendered(path));
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// Store the current zoom and pan for the open board so it can be
+    /// restored the next time the board is opened.
+    pub fn remember_board_view(&mut self) {
+        let Some(path) = &self.current_file_path else {
+            return;
+        };
+        if let Some(entry) = self.recent_boards.iter_mut().find(|e| &e.path == path) {
+            entry.zoom = self.zoom;
+            entry.pan_offset = [self.pan_offset.x, self.pan_offset.y];
+        }
+    }
+
+    /// Apply the view a board was last left in, overriding the one saved in
+    /// the file. Does nothing unless view restoring is enabled.
+    pub fn apply_remembered_view(&mut self, path: &Path) {
+        if !self.restore_board_view {
+            return;
+        }
+        if let Some(entry) = self.recent_boards.iter().find(|e| e.path == path) {
+            self.zoom = entry.zoom;
+            self.pan_offset = egui::vec2(entry.pan_offset[0], entry.pan_offset[1]);
+        }
+    }
+
+    /// Thumbnail texture of a recent board, loaded from disk on first use.
+    pub fn recent_thumbnail(
+        &mut self,
+        path: &Path,
+        ctx: &egui::Context,
+    ) -> Option<egui::TextureHandle> {
+        if let Some(cached) = self.recent_thumbnails.get(path) {
+            return cached.clone();
+        }
+        let texture = thumbnail_path(path)
+            .and_then(|thumb| std::fs::read(thumb).ok())
+            .and_then(|bytes| image::load_from_memory(&bytes).ok())
+            .map(|img| {
+                let rgba = img.to_rgba8();
+                let color_img = egui::ColorImage::from_rgba_unmultiplied(
+                    [rgba.width() as usize, rgba.height() as usize],
+                    &rgba.into_raw(),
+                );
+                ctx.load_texture(
+                    format!("recent_thumbnail_{}", board_key(Some(path))),
+                    color_img,
+                    egui::TextureOptions::LINEAR,
+                )
+            });
+        self.recent_thumbnails
+            .insert(path.to_path_buf(), texture.clone());
+        texture
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touch_recent_moves_to_front_and_caps_list() {
+        let mut list = Vec::new();
+        for i in 0..MAX_RECENT_BOARDS + 2 {
+            touch_recent(&mut list, Path::new(&format!("/b/{}.kugel", i)), i as u64, 1.0, [0.0, 0.0]);
+        }
+        assert_eq!(list.len(), MAX_RECENT_BOARDS);
+        assert_eq!(list[0].path, Path::new(&format!("/b/{}.kugel", MAX_RECENT_BOARDS + 1)));
+
+        touch_recent(&mut list, Path::new("/b/5.kugel"), 100, 2.0, [10.0, 20.0]);
+        assert_eq!(list.len(), MAX_RECENT_BOARDS);
+        assert_eq!(list[0].path, Path::new("/b/5.kugel"));
+        assert_eq!(list[0].zoom, 2.0);
+        assert_eq!(list.iter().filter(|e| e.path == Path::new("/b/5.kugel")).count(), 1);
+    }
+
+    #[test]
+    fn test_apply_remembered_view_only_when_enabled() {
+        let mut app = App::default();
+        let path = Path::new("/b/board.kugel");
+        touch_recent(&mut app.recent_boards, path, 0, 3.0, [40.0, 50.0]);
+
+        app.apply_remembered_view(path);
+        assert_eq!(app.zoom, 1.0);
+
+        app.restore_board_view = true;
+        app.apply_remembered_view(path);
+        assert_eq!(app.zoom, 3.0);
+        assert_eq!(app.pan_offset, egui::vec2(40.0, 50.0));
+    }
+}
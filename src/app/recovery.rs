@@ -0,0 +1,258 @@
+use crate::app::App;
+use crate::assets::content_hash;
+use crate::state::backup::write_atomic;
+use crate::state::container::load_board;
+use crate::updater::UiEvent;
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often a dirty board is written to the recovery directory.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Autosaved copy of a board, found in the recovery directory on launch.
+pub struct RecoveryFile {
+    pub path: PathBuf,
+    /// Board the autosave belongs to; `None` for boards that were never saved.
+    pub source: Option<PathBuf>,
+    pub modified: SystemTime,
+}
+
+/// `<eframe storage dir>/recovery`, created on demand.
+pub fn recovery_dir() -> Option<PathBuf> {
+    eframe::storage_dir("Kugel").map(|dir| dir.join("recovery"))
+}
+
+/// File name stem identifying a board across launches, used for its
+/// recovery file and recent-files thumbnail.
+pub fn board_key(source: Option<&Path>) -> String {
+    match source {
+        Some(path) => {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "board".to_string());
+            let hash = content_hash(path.to_string_lossy().as_bytes());
+            format!("{}-{}", stem, &hash[..12])
+        }
+        None => "untitled".to_string(),
+    }
+}
+
+fn source_sidecar(recovery_path: &Path) -> PathBuf {
+    recovery_path.with_extension("source")
+}
+
+/// Recovery files in `dir` that are newer than the board they belong to,
+/// newest first. Stale autosaves (the board was saved afterwards) are deleted.
+pub fn scan_recovery_dir(dir: &Path) -> Vec<RecoveryFile> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("kugel") {
+            continue;
+        }
+        let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else {
+            continue;
+        };
+        let source = std::fs::read_to_string(source_sidecar(&path))
+            .ok()
+            .map(|s| PathBuf::from(s.trim()));
+
+        let source_modified = source
+            .as_ref()
+            .and_then(|s| std::fs::metadata(s).and_then(|m| m.modified()).ok());
+        if source_modified.is_some_and(|disk| disk >= modified) {
+            remove_recovery_file(&path);
+            continue;
+        }
+        found.push(RecoveryFile {
+            path,
+            source,
+            modified,
+        });
+    }
+    found.sort_by_key(|f| std::cmp::Reverse(f.modified));
+    found
+}
+
+pub fn remove_recovery_file(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(source_sidecar(path));
+}
+
+/// Human readable age of a file, e.g. "5 minutes ago".
+pub fn describe_age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    let (amount, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        _ => (secs / 86_400, "day"),
+    };
+    format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/// Local date and time, e.g. "Oct 18, 2026 14:05:09".
+pub fn describe_time(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(time)
+        .format("%b %-d, %Y %H:%M:%S")
+        .to_string()
+}
+
+impl App {
+    /// Write the board to the recovery directory on a background thread when it
+    /// has unsaved changes and the last autosave is older than `AUTOSAVE_INTERVAL`.
+    pub fn autosave_if_due(&mut self, ctx: &egui::Context) {
+        if !self.is_dirty || self.autosave_in_flight {
+            return;
+        }
+        let elapsed = self.last_autosave.elapsed();
+        if elapsed < AUTOSAVE_INTERVAL {
+            ctx.request_repaint_after(AUTOSAVE_INTERVAL - elapsed);
+            return;
+        }
+        let empty_unsaved = self.canvas.shapes.is_empty() && self.current_file_path.is_none();
+        let Some(dir) = recovery_dir() else {
+            return;
+        };
+        self.last_autosave = Instant::now();
+        if empty_unsaved {
+            return;
+        }
+
+        let state = self.board_state();
+        let source = self.current_file_path.clone();
+        let target = dir.join(format!("{}.kugel", board_key(source.as_deref())));
+        let ui_tx = self.ui_event_tx.clone();
+        let ctx_clone = ctx.clone();
+        self.autosave_in_flight = true;
+
+        std::thread::spawn(move || {
+            let result = (|| -> Result<(), String> {
+                std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                write_atomic(&target, state)?;
+                if let Some(source) = source {
+                    std::fs::write(source_sidecar(&target), source.to_string_lossy().as_bytes())
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            })();
+            let _ = ui_tx.send(UiEvent::AutosaveFinished {
+                path: target,
+                error: result.err(),
+            });
+            ctx_clone.request_repaint();
+        });
+    }
+
+    /// Remove the autosave of the current board, e.g. after it was saved or
+    /// its changes were deliberately discarded.
+    pub fn discard_recovery(&self) {
+        if let Some(dir) = recovery_dir() {
+            let key = board_key(self.current_file_path.as_deref());
+            remove_recovery_file(&dir.join(format!("{}.kugel", key)));
+        }
+    }
+
+    /// Offer to restore autosaves left behind by a crash. Returns true when a
+    /// board was restored.
+    pub fn offer_recovery(&mut self, ctx: &egui::Context) -> bool {
+        let Some(dir) = recovery_dir() else {
+            return false;
+        };
+        for candidate in scan_recovery_dir(&dir) {
+            let name = candidate
+                .source
+                .as_ref()
+                .and_then(|s| s.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "an unsaved board".to_string());
+            let confirm = rfd::MessageDialog::new()
+                .set_title("Recover Unsaved Changes")
+                .set_description(format!(
+                    "Kugel found unsaved changes to {} from {}. Do you want to restore them?",
+                    name,
+                    describe_age(candidate.modified)
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm != rfd::MessageDialogResult::Yes {
+                remove_recovery_file(&candidate.path);
+                continue;
+            }
+
+            match load_board(&candidate.path) {
+                Ok(state) => {
+                    self.apply_board_state(state, ctx);
+                    self.current_file_path = candidate.source;
+                    self.record_file_mtime();
+                    self.is_dirty = true;
+                    self.notification = Some((
+                        format!("Restored unsaved changes to {}", name),
+                        Instant::now(),
+                    ));
+                    return true;
+                }
+                Err(e) => {
+                    self.notification = Some((
+                        format!("Restoring unsaved changes failed: {}", e),
+                        Instant::now(),
+                    ));
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_key() {
+        assert_eq!(board_key(None), "untitled");
+        let a = board_key(Some(Path::new("/boards/ideas.kugel")));
+        let b = board_key(Some(Path::new("/other/ideas.kugel")));
+        assert!(a.starts_with("ideas-"));
+        assert_ne!(a, b);
+        assert_eq!(a, board_key(Some(Path::new("/boards/ideas.kugel"))));
+    }
+
+    #[test]
+    fn test_describe_age() {
+        let now = SystemTime::now();
+        assert_eq!(describe_age(now), "just now");
+        assert_eq!(describe_age(now - Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(describe_age(now - Duration::from_secs(3 * 3600)), "3 hours ago");
+        assert_eq!(describe_age(now - Duration::from_secs(2 * 86_400)), "2 days ago");
+    }
+
+    #[test]
+    fn test_scan_skips_autosaves_older_than_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = dir.path().join("board.kugel");
+        let recovery = dir.path().join("recovery");
+        std::fs::create_dir_all(&recovery).unwrap();
+
+        let stale = recovery.join("stale.kugel");
+        std::fs::write(&stale, b"old").unwrap();
+        std::fs::write(source_sidecar(&stale), board.to_string_lossy().as_bytes()).unwrap();
+        std::fs::write(recovery.join("untitled.kugel"), b"new").unwrap();
+        // The board on disk is written after the stale autosave.
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&board, b"saved").unwrap();
+
+        let found = scan_recovery_dir(&recovery);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].source.is_none());
+        assert!(!stale.exists());
+    }
+}
@@ -1,6 +1,27 @@
 use crate::app::App;
-use crate::shapes::ShapeData;
+use crate::shapes::{connector, ShapeData};
 use eframe::egui;
+use std::collections::HashMap;
+
+/// Angle the rotation handle snaps to while Shift is held (15°).
+pub const ROTATION_SNAP_STEP: f32 = std::f32::consts::PI / 12.0;
+
+/// Distance in screen pixels between the selection and its rotation handle.
+const ROTATION_HANDLE_OFFSET: f32 = 24.0;
+
+/// Rotation handle drag in progress.
+#[derive(Clone, Copy)]
+pub struct RotationDrag {
+    /// Canvas point the selection turns around.
+    pub pivot: egui::Pos2,
+    /// Pointer angle around `pivot` when the drag started.
+    pub start_angle: f32,
+    /// Rotation of a single selected shape when the drag started, so Shift
+    /// snaps its absolute angle. Zero for groups, where the turn itself snaps.
+    pub base: f32,
+    /// Turn applied to the selection so far.
+    pub applied: f32,
+}
 
 impl App {
     /// Clear selection and select a single shape.
@@ -13,18 +34,16 @@ impl App {
     /// Select all shapes.
     pub fn select_all(&mut self) {
         self.selected_shape_indices.clear();
-        for idx in 0..self.canvas.shapes.len() {
-            self.selected_shape_indices.insert(idx);
-        }
-        if !self.canvas.shapes.is_empty() {
-            self.primary_selected = Some(self.canvas.shapes.len() - 1);
-        } else {
-            self.primary_selected = None;
+        for (idx, shape) in self.canvas.shapes.iter().enumerate() {
+            if !shape.locked {
+                self.selected_shape_indices.insert(idx);
+            }
         }
+        self.primary_selected = self.selected_shape_indices.iter().max().copied();
     }
 
-    /// Duplicate all selected shapes in place and select the copies.
-    pub fn duplicate_selection(&mut self, ctx: &egui::Context) {
+    /// Duplicate all selected shapes moved by `offset` and select the copies.
+    pub fn duplicate_selection(&mut self, offset: egui::Vec2, ctx: &egui::Context) {
         if self.selected_shape_indices.is_empty() {
             return;
         }
@@ -36,18 +55,26 @@ impl App {
         indices.sort_unstable();
         self.clear_selection();
 
+        let first_new = self.canvas.shapes.len();
+        let mut ids = HashMap::new();
         for idx in indices {
             if idx < self.canvas.shapes.len() {
                 let mut dup = self.canvas.shapes[idx].clone();
+                dup.data.translate(offset);
+                ids.insert(dup.id, self.canvas.next_id);
                 dup.id = self.canvas.next_id;
                 self.canvas.next_id += 1;
-                dup.data.load_textures(ctx, dup.id);
+                self.canvas.assets.load_texture(&mut dup.data, ctx);
                 self.canvas.shapes.push(dup);
                 let new_idx = self.canvas.shapes.len() - 1;
                 self.selected_shape_indices.insert(new_idx);
                 self.primary_selected = Some(new_idx);
             }
         }
+        let mut copies = self.canvas.shapes.split_off(first_new);
+        connector::remap_connector_ids(&mut copies, &ids);
+        self.canvas.regroup(&mut copies);
+        self.canvas.shapes.extend(copies);
     }
 
     /// Clear all selection.
@@ -83,7 +110,7 @@ impl App {
             if self.selected_shape_indices.contains(&i) {
                 continue;
             }
-            let t = shape.data.get_bounds();
+            let t = shape.get_bounds();
             if !t.is_positive() {
                 continue;
             }
@@ -160,20 +187,20 @@ impl App {
         let tolerance = 5.0;
         // First pass: non-section shapes (topmost first)
         for (idx, shape) in self.canvas.shapes.iter().enumerate().rev() {
-            if matches!(shape.data, ShapeData::SectionBox { .. }) {
+            if shape.locked || matches!(shape.data, ShapeData::SectionBox { .. }) {
                 continue;
             }
-            if shape.data.contains_point(canvas_pos, tolerance) {
+            if shape.contains_point(canvas_pos, tolerance) {
                 return Some(idx);
             }
         }
         // Second pass: sections as fallback — clicking empty space inside a
         // section selects it, but shapes on top take priority
         for (idx, shape) in self.canvas.shapes.iter().enumerate().rev() {
-            if !matches!(shape.data, ShapeData::SectionBox { .. }) {
+            if shape.locked || !matches!(shape.data, ShapeData::SectionBox { .. }) {
                 continue;
             }
-            if shape.data.contains_point(canvas_pos, tolerance) {
+            if shape.contains_point(canvas_pos, tolerance) {
                 return Some(idx);
             }
         }
@@ -192,13 +219,16 @@ impl App {
             .iter()
             .enumerate()
             .filter(|(idx, shape)| {
-                if *idx == section_idx || self.selected_shape_indices.contains(idx) {
+                if *idx == section_idx
+                    || shape.locked
+                    || self.selected_shape_indices.contains(idx)
+                {
                     return false;
                 }
                 if matches!(shape.data, ShapeData::SectionBox { .. }) {
                     return false;
                 }
-                let bounds = shape.data.get_bounds();
+                let bounds = shape.get_bounds();
                 bounds.is_positive() && section_rect.intersects(bounds)
             })
             .map(|(idx, _)| idx)
@@ -207,40 +237,91 @@ impl App {
 
     pub fn get_handle_under_mouse(&self, shape_idx: usize, mouse_pos: egui::Pos2) -> Option<usize> {
         let shape = &self.canvas.shapes[shape_idx];
-        let bounds = shape.data.get_bounds();
-        let screen_bounds = egui::Rect::from_min_max(
-            self.canvas_to_screen(bounds.min),
-            self.canvas_to_screen(bounds.max),
-        );
+        let corners = shape.corners().map(|p| self.canvas_to_screen(p));
         let is_text_or_sticky = matches!(
             shape.data,
             ShapeData::Text { .. } | ShapeData::StickyNote { .. }
         );
+        let handles: &[usize] = if is_text_or_sticky {
+            &[1, 3]
+        } else {
+            &[0, 1, 2, 3]
+        };
+        handles
+            .iter()
+            .copied()
+            .find(|&h_idx| mouse_pos.distance(corners[h_idx]) <= 8.0)
+    }
 
-        if is_text_or_sticky {
-            let handles = [
-                (1, screen_bounds.right_top()),
-                (3, screen_bounds.right_bottom()),
-            ];
-            for &(h_idx, pos) in &handles {
-                if mouse_pos.distance(pos) <= 8.0 {
-                    return Some(h_idx);
-                }
+    /// Screen positions of the rotation handle and of the edge midpoint it
+    /// hangs from, plus the canvas point the selection turns around. A single
+    /// shape's handle follows its rotation; a group's sits above its bounds.
+    pub fn rotation_handle(&self) -> Option<(egui::Pos2, egui::Pos2, egui::Pos2)> {
+        if self.selected_shape_indices.len() == 1 {
+            let idx = self.primary_selected?;
+            let shape = self.canvas.shapes.get(idx)?;
+            if !shape.data.get_bounds().is_positive() {
+                return None;
             }
+            let [top_left, top_right, ..] = shape.corners();
+            let edge = self.canvas_to_screen(top_left.lerp(top_right, 0.5));
+            let up = egui::emath::Rot2::from_angle(shape.rotation) * egui::vec2(0.0, -1.0);
+            Some((edge + up * ROTATION_HANDLE_OFFSET, edge, shape.pivot()))
         } else {
-            let handle_positions = [
-                screen_bounds.left_top(),     // 0
-                screen_bounds.right_top(),    // 1
-                screen_bounds.left_bottom(),  // 2
-                screen_bounds.right_bottom(), // 3
-            ];
-            for (h_idx, &pos) in handle_positions.iter().enumerate() {
-                if mouse_pos.distance(pos) <= 8.0 {
-                    return Some(h_idx);
-                }
+            let bounds = self.selection_bounds()?;
+            let edge = self.canvas_to_screen(bounds.center_top());
+            Some((
+                edge - egui::vec2(0.0, ROTATION_HANDLE_OFFSET),
+                edge,
+                bounds.center(),
+            ))
+        }
+    }
+
+    pub fn rotation_handle_under_mouse(&self, mouse_pos: egui::Pos2) -> bool {
+        self.rotation_handle()
+            .is_some_and(|(handle, _, _)| mouse_pos.distance(handle) <= 8.0)
+    }
+
+    /// Start turning the selection with the rotation handle, as one undo step.
+    pub fn begin_rotation(&mut self, mouse_pos: egui::Pos2) {
+        let Some((_, _, pivot)) = self.rotation_handle() else {
+            return;
+        };
+        let base = match self.primary_selected {
+            Some(idx) if self.selected_shape_indices.len() == 1 => {
+                self.canvas.shapes[idx].rotation
+            }
+            _ => 0.0,
+        };
+        self.canvas.push_history();
+        self.rotation_drag = Some(RotationDrag {
+            pivot,
+            start_angle: (self.screen_to_canvas(mouse_pos) - pivot).angle(),
+            base,
+            applied: 0.0,
+        });
+    }
+
+    /// Turn the selection to follow the pointer at `canvas_pos`. With `snap`
+    /// the resulting angle is rounded to `ROTATION_SNAP_STEP`.
+    pub fn update_rotation(&mut self, canvas_pos: egui::Pos2, snap: bool) {
+        let Some(drag) = self.rotation_drag.as_mut() else {
+            return;
+        };
+        let mut turn = (canvas_pos - drag.pivot).angle() - drag.start_angle;
+        if snap {
+            let snapped = ((drag.base + turn) / ROTATION_SNAP_STEP).round() * ROTATION_SNAP_STEP;
+            turn = snapped - drag.base;
+        }
+        let step = turn - drag.applied;
+        drag.applied = turn;
+        let pivot = drag.pivot;
+        for &idx in &self.selected_shape_indices {
+            if let Some(shape) = self.canvas.shapes.get_mut(idx) {
+                shape.rotate_about(pivot, step);
             }
         }
-        None
     }
 
     /// Union bounds (canvas) of all selected shapes.
@@ -248,7 +329,7 @@ impl App {
         let mut acc: Option<egui::Rect> = None;
         for &idx in &self.selected_shape_indices {
             if idx < self.canvas.shapes.len() {
-                let b = self.canvas.shapes[idx].data.get_bounds();
+                let b = self.canvas.shapes[idx].get_bounds();
                 if b.is_positive() {
                     acc = Some(acc.map_or(b, |a| a.union(b)));
                 }
@@ -300,28 +381,28 @@ impl App {
 
         // Sort shapes left-to-right by their bounding box min.x
         sorted_indices.sort_by(|&a, &b| {
-            let rect_a = self.canvas.shapes[a].data.get_bounds();
-            let rect_b = self.canvas.shapes[b].data.get_bounds();
+            let rect_a = self.canvas.shapes[a].get_bounds();
+            let rect_b = self.canvas.shapes[b].get_bounds();
             rect_a.min.x.partial_cmp(&rect_b.min.x).unwrap_or(std::cmp::Ordering::Equal)
         });
 
         // Find the top-most position (minimum min.y) among the selected shapes
         let top_y = sorted_indices
             .iter()
-            .map(|&idx| self.canvas.shapes[idx].data.get_bounds().min.y)
+            .map(|&idx| self.canvas.shapes[idx].get_bounds().min.y)
             .fold(f32::INFINITY, f32::min);
 
         let gap = 16.0;
-        let mut next_x = self.canvas.shapes[sorted_indices[0]].data.get_bounds().min.x;
+        let mut next_x = self.canvas.shapes[sorted_indices[0]].get_bounds().min.x;
 
         for &idx in &sorted_indices {
-            let bounds = self.canvas.shapes[idx].data.get_bounds();
+            let bounds = self.canvas.shapes[idx].get_bounds();
             let delta_x = next_x - bounds.min.x;
             let delta_y = top_y - bounds.min.y;
 
             self.canvas.shapes[idx].data.translate(egui::vec2(delta_x, delta_y));
 
-            let new_bounds = self.canvas.shapes[idx].data.get_bounds();
+            let new_bounds = self.canvas.shapes[idx].get_bounds();
             next_x = new_bounds.max.x + gap;
         }
 
@@ -396,4 +477,29 @@ mod tests {
         assert_eq!(app.canvas.shapes[idx1].data.get_bounds().min, egui::pos2(50.0, 500.0));
         assert_eq!(app.canvas.shapes[idx2].data.get_bounds().min, egui::pos2(400.0, 100.0));
     }
+
+    #[test]
+    fn test_rotation_handle_snaps_with_shift() {
+        let mut app = App::default();
+        let rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(100.0, 100.0));
+        app.canvas.shapes.push(crate::shapes::Shape::new_rect(1, rect, egui::Color32::RED, 1.0, false));
+        app.select_single(0);
+
+        let (handle, _, pivot) = app.rotation_handle().unwrap();
+        assert_eq!(pivot, egui::pos2(50.0, 50.0));
+        app.begin_rotation(handle);
+
+        // Pointer dragged to 20° clockwise of straight up
+        let angle = (-90.0_f32 + 20.0).to_radians();
+        let pointer = pivot + egui::vec2(angle.cos(), angle.sin()) * 80.0;
+        app.update_rotation(pointer, false);
+        assert!((app.canvas.shapes[0].rotation - 20.0_f32.to_radians()).abs() < 1e-4);
+        app.update_rotation(pointer, true);
+        assert!((app.canvas.shapes[0].rotation - ROTATION_SNAP_STEP).abs() < 1e-4);
+        assert_eq!(app.canvas.shapes[0].pivot(), pivot);
+
+        app.canvas.undo();
+        assert_eq!(app.canvas.shapes[0].rotation, 0.0);
+    }
 }
+
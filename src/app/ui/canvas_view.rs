@@ -1,3 +1,5 @@
+use crate::app::arrange::ZOrder;
+use crate::app::lock::draw_lock_badge;
 use crate::app::App;
 use crate::image_utils::process_file_to_images;
 use crate::shapes::{ShapeData, Tool};
@@ -174,6 +176,10 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                     app.tool = Tool::Section;
                     app.clear_selection();
                 }
+                if bare_key(ui, egui::Key::C) {
+                    app.tool = Tool::Connector;
+                    app.clear_selection();
+                }
 
                 if bare_key(ui, egui::Key::I) {
                     app.import_image_dialog(ctx);
@@ -193,13 +199,17 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                 app.is_dirty = true;
             }
             if has_shortcut(ui, egui::Key::S, true) {
-                app.save();
+                app.save(ctx);
             }
             if has_shortcut(ui, egui::Key::O, true) {
-                app.open_file_dialog(ctx);
+                if ui.input(|i| i.modifiers.shift) {
+                    app.show_recent_dialog = true;
+                } else {
+                    app.open_file_dialog(ctx);
+                }
             }
             if has_shortcut(ui, egui::Key::N, true) {
-                app.new_board();
+                app.new_board(ctx);
             }
             if has_shortcut(ui, egui::Key::E, true) {
                 app.show_export_dialog = true;
@@ -223,40 +233,55 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                 app.declutter_selection();
             }
 
-            // Duplicate selection (Cmd/Ctrl + D)
-            if has_shortcut(ui, egui::Key::D, true) {
-                if let Some(&idx) = app.primary_selected.as_ref() {
-                    if idx < app.canvas.shapes.len() {
-                        app.canvas.history.push(app.canvas.shapes.clone());
-                        app.canvas.undo_history.clear();
-                        app.is_dirty = true;
+            // Group (Cmd/Ctrl + G) and ungroup (Cmd/Ctrl + Shift + G)
+            if app.editing_text_index.is_none() && has_shortcut(ui, egui::Key::G, true) {
+                if ui.input(|i| i.modifiers.shift) {
+                    app.ungroup_selection();
+                } else {
+                    app.group_selection();
+                }
+            }
 
-                        let mut dup = app.canvas.shapes[idx].clone();
-                        dup.data.translate(egui::vec2(20.0, 20.0));
-                        dup.id = app.canvas.next_id;
-                        app.canvas.next_id += 1;
-                        dup.data.load_textures(ctx, dup.id);
+            // Lock selection (Cmd/Ctrl + Shift + L)
+            if app.editing_text_index.is_none()
+                && has_shortcut(ui, egui::Key::L, true)
+                && ui.input(|i| i.modifiers.shift)
+            {
+                app.lock_selection();
+            }
 
-                        app.canvas.shapes.push(dup);
-                        app.select_single(app.canvas.shapes.len() - 1);
-                        app.notification = Some((
-                            "Duplicated selection".to_string(),
-                            Instant::now(),
-                        ));
-                    }
+            // Stacking order (Cmd/Ctrl + ] and [, with Shift for front and back).
+            // Shift + bracket arrives as a curly bracket on most layouts.
+            if app.editing_text_index.is_none() {
+                let shift = ui.input(|i| i.modifiers.shift);
+                if has_shortcut(ui, egui::Key::CloseCurlyBracket, true) {
+                    app.restack_selection(ZOrder::ToFront);
+                } else if has_shortcut(ui, egui::Key::CloseBracket, true) {
+                    app.restack_selection(if shift { ZOrder::ToFront } else { ZOrder::Forward });
+                }
+                if has_shortcut(ui, egui::Key::OpenCurlyBracket, true) {
+                    app.restack_selection(ZOrder::ToBack);
+                } else if has_shortcut(ui, egui::Key::OpenBracket, true) {
+                    app.restack_selection(if shift { ZOrder::ToBack } else { ZOrder::Backward });
                 }
             }
 
+            // Duplicate selection (Cmd/Ctrl + D)
+            if has_shortcut(ui, egui::Key::D, true) && app.has_selection() {
+                app.duplicate_selection(egui::vec2(20.0, 20.0), ctx);
+                app.notification = Some((
+                    "Duplicated selection".to_string(),
+                    Instant::now(),
+                ));
+            }
+
             // Copy selection (Cmd/Ctrl + C)
+            // Copy selection as PNG (Cmd/Ctrl + Shift + C)
             if has_shortcut(ui, egui::Key::C, true) {
-                if let Some(&idx) = app.primary_selected.as_ref() {
-                    if idx < app.canvas.shapes.len() {
-                        app.copied_shape = Some(app.canvas.shapes[idx].clone());
-                        app.notification = Some((
-                            "Copied shape to buffer".to_string(),
-                            Instant::now(),
-                        ));
-                    }
+                if ui.input(|i| i.modifiers.shift) {
+                    app.copy_selection_as_png(ctx);
+                } else {
+                    app.copy_selection();
                 }
             }
 
@@ -306,8 +331,10 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                     app.canvas.undo_history.clear();
                     app.is_dirty = true;
                     for &idx in &app.selected_shape_indices {
-                        if idx < app.canvas.shapes.len() {
-                            app.canvas.shapes[idx].data.translate(nudge_delta);
+                        if let Some(shape) = app.canvas.shapes.get_mut(idx) {
+                            if !shape.locked {
+                                shape.data.translate(nudge_delta);
+                            }
                         }
                     }
                 }
@@ -337,6 +364,11 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                 for file in dropped_files {
                     if let Some(path) = &file.path {
                         if path.extension().map_or(false, |ext| ext == "kugel") {
+                            // Shift + drop merges the board instead of replacing this one
+                            if ui.input(|i| i.modifiers.shift) {
+                                app.insert_board(path, target_canvas, ctx);
+                                continue;
+                            }
                             let mut proceed = true;
                             if !app.canvas.shapes.is_empty() {
                                 let confirm = rfd::MessageDialog::new()
@@ -349,7 +381,7 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
 
                                 match confirm {
                                     rfd::MessageDialogResult::Yes => {
-                                        proceed = app.save();
+                                        proceed = app.save(ctx);
                                     }
                                     rfd::MessageDialogResult::No => {
                                         proceed = true;
@@ -503,8 +535,11 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                         if marquee_box.width() > 2.0 && marquee_box.height() > 2.0 {
                             app.clear_selection();
                             for (idx, shape) in app.canvas.shapes.iter().enumerate() {
+                                if shape.locked {
+                                    continue;
+                                }
                                 let is_section = matches!(shape.data, ShapeData::SectionBox { .. });
-                                let shape_bounds = shape.data.get_bounds();
+                                let shape_bounds = shape.get_bounds();
                                 let selected = if is_section {
                                     marquee_box.contains_rect(shape_bounds)
                                 } else {
@@ -515,9 +550,11 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                     app.primary_selected = Some(idx);
                                 }
                             }
+                            app.expand_selection_to_groups();
                         }
                     }
                     app.is_resizing = None;
+                    app.rotation_drag = None;
                     app.is_dragging_shape = false;
                     app.snap_correction = egui::Vec2::ZERO;
                     app.marquee_start = None;
@@ -543,7 +580,6 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                             if let Some(edit_idx) = app.editing_text_index {
                                 let clicked_edited_shape = edit_idx < app.canvas.shapes.len()
                                     && app.canvas.shapes[edit_idx]
-                                        .data
                                         .contains_point(click_canvas_pos, 5.0);
                                 if !clicked_edited_shape {
                                     app.editing_text_index = None;
@@ -554,7 +590,10 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
 
                             let mut clicked_handle = false;
 
-                            if app.selected_shape_indices.len() > 1 {
+                            if app.rotation_handle_under_mouse(click_pos) {
+                                app.begin_rotation(click_pos);
+                                clicked_handle = true;
+                            } else if app.selected_shape_indices.len() > 1 {
                                 if let Some(handle_idx) =
                                     app.group_handle_under_mouse(click_pos)
                                 {
@@ -580,21 +619,12 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                 if let Some(idx) = app.hit_test(click_canvas_pos) {
                                     let shift = ui.input(|i| i.modifiers.shift);
                                     if shift {
-                                        if app.selected_shape_indices.contains(&idx) {
-                                            app.selected_shape_indices.remove(&idx);
-                                            if app.primary_selected == Some(idx) {
-                                                app.primary_selected =
-                                                    app.selected_shape_indices.iter().next().copied();
-                                            }
-                                        } else {
-                                            app.selected_shape_indices.insert(idx);
-                                            app.primary_selected = Some(idx);
-                                        }
+                                        app.toggle_with_group(idx);
                                     } else if !app.selected_shape_indices.contains(&idx) {
-                                        app.select_single(idx);
+                                        app.select_with_group(idx);
                                     }
                                     if ui.input(|i| i.modifiers.alt) {
-                                        app.duplicate_selection(ctx);
+                                        app.duplicate_selection(egui::Vec2::ZERO, ctx);
                                     }
                                     app.canvas.push_history();
                                     app.is_dragging_shape = true;
@@ -604,6 +634,7 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                 } else {
                                     if !ui.input(|i| i.modifiers.shift) {
                                         app.clear_selection();
+                                        app.entered_group = None;
                                     }
                                     app.marquee_start = Some(click_canvas_pos);
                                 }
@@ -629,9 +660,11 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                 if let Some(url) = url {
                                     ctx.open_url(egui::OpenUrl::new_tab(url));
                                 } else if !shift {
-                                    app.select_single(idx);
+                                    app.select_with_group(idx);
                                 }
                                 app.marquee_start = None;
+                            } else if let Some(idx) = app.lock_badge_under_mouse(pos) {
+                                app.unlock_shape(idx);
                             } else if !ui.input(|i| i.modifiers.shift) {
                                 app.clear_selection();
                             }
@@ -643,10 +676,16 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                         }) && response.hovered()
                         {
                             if let Some(idx) = app.hit_test(canvas_pos) {
-                                let text_opt = match &app.canvas.shapes[idx].data {
-                                    ShapeData::Text { text, .. }
-                                    | ShapeData::StickyNote { text, .. } => Some(text.clone()),
-                                    _ => None,
+                                let text_opt = if app.enter_group(idx) {
+                                    app.marquee_start = None;
+                                    None
+                                } else {
+                                    match &app.canvas.shapes[idx].data {
+                                        ShapeData::Text { text, .. }
+                                        | ShapeData::StickyNote { text, .. } => Some(text.clone()),
+                                        ShapeData::Connector { label, .. } => Some(label.clone()),
+                                        _ => None,
+                                    }
                                 };
                                 if let Some(text) = text_opt {
                                     app.canvas.push_history();
@@ -662,7 +701,10 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
 
                         if response.dragged() {
                             let delta = response.drag_delta() / app.zoom;
-                            if let Some(handle_idx) = app.is_resizing {
+                            if app.rotation_drag.is_some() {
+                                let snap = ui.input(|i| i.modifiers.shift);
+                                app.update_rotation(canvas_pos, snap);
+                            } else if let Some(handle_idx) = app.is_resizing {
                                 if app.selected_shape_indices.len() > 1 {
                                     if let Some(bounds) = app.selection_bounds() {
                                         let anchor = match handle_idx {
@@ -694,9 +736,7 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                     }
                                 } else if let Some(primary_idx) = app.primary_selected {
                                     if primary_idx < app.canvas.shapes.len() {
-                                        app.canvas.shapes[primary_idx]
-                                            .data
-                                            .resize(handle_idx, delta, canvas_pos);
+                                        app.canvas.shapes[primary_idx].resize(handle_idx, delta, canvas_pos);
                                     }
                                 }
                             } else if app.is_dragging_shape {
@@ -731,7 +771,7 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                                 let mut correction = egui::Vec2::ZERO;
                                 if let Some(p) = app.primary_selected {
                                     if p < app.canvas.shapes.len() {
-                                        let moving = app.canvas.shapes[p].data.get_bounds();
+                                        let moving = app.canvas.shapes[p].get_bounds();
                                         let (corr, guides) = app
                                             .compute_alignment_snap(moving, 6.0 / app.zoom);
                                         correction = corr;
@@ -771,7 +811,11 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                             );
                         }
 
-                        if app.selected_shape_indices.len() > 1 {
+                        if app.rotation_drag.is_some() {
+                            ctx.set_cursor_icon(egui::CursorIcon::Grabbing);
+                        } else if app.rotation_handle_under_mouse(pos) {
+                            ctx.set_cursor_icon(egui::CursorIcon::Grab);
+                        } else if app.selected_shape_indices.len() > 1 {
                             if let Some(handle_idx) = app.group_handle_under_mouse(pos) {
                                 let cursor = match handle_idx {
                                     0 | 3 => egui::CursorIcon::ResizeNwSe,
@@ -875,6 +919,7 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
             }
 
             // Draw canvas elements
+            app.canvas.route_connectors();
             painter.set_clip_rect(response.rect);
             app.canvas.render(
                 &painter,
@@ -887,39 +932,36 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
             if app.tool == Tool::Select {
                 for &idx in &app.selected_shape_indices {
                     if idx < app.canvas.shapes.len() {
-                        let bounds = app.canvas.shapes[idx].data.get_bounds();
-                        if bounds.is_positive() {
-                            let screen_bounds = egui::Rect::from_min_max(
-                                app.canvas_to_screen(bounds.min),
-                                app.canvas_to_screen(bounds.max),
-                            );
-
-                            painter.rect_stroke(
-                                screen_bounds,
-                                0.0,
-                                egui::Stroke::new(1.5, egui::Color32::from_rgb(99, 102, 241)),
-                                egui::StrokeKind::Outside,
-                            );
+                        let shape = &app.canvas.shapes[idx];
+                        if shape.data.get_bounds().is_positive() {
+                            let corners = shape.corners().map(|p| app.canvas_to_screen(p));
+                            let outline =
+                                egui::Stroke::new(1.5, egui::Color32::from_rgb(99, 102, 241));
+                            if shape.rotation != 0.0 {
+                                painter.add(egui::Shape::closed_line(
+                                    vec![corners[0], corners[1], corners[3], corners[2]],
+                                    outline,
+                                ));
+                            } else {
+                                painter.rect_stroke(
+                                    egui::Rect::from_min_max(corners[0], corners[3]),
+                                    0.0,
+                                    outline,
+                                    egui::StrokeKind::Outside,
+                                );
+                            }
 
                             if app.primary_selected == Some(idx)
                                 && app.selected_shape_indices.len() == 1
                             {
                                 let is_text_or_sticky = matches!(
-                                    app.canvas.shapes[idx].data,
+                                    shape.data,
                                     ShapeData::Text { .. } | ShapeData::StickyNote { .. }
                                 );
                                 let handle_positions = if is_text_or_sticky {
-                                    vec![
-                                        screen_bounds.right_top(),
-                                        screen_bounds.right_bottom(),
-                                    ]
+                                    vec![corners[1], corners[3]]
                                 } else {
-                                    vec![
-                                        screen_bounds.left_top(),
-                                        screen_bounds.right_top(),
-                                        screen_bounds.left_bottom(),
-                                        screen_bounds.right_bottom(),
-                                    ]
+                                    corners.to_vec()
                                 };
                                 for &h_pos in &handle_positions {
                                     painter.rect(
@@ -970,6 +1012,31 @@ pub fn render_central_canvas(app: &mut App, ctx: &egui::Context, is_dark: bool)
                         }
                     }
                 }
+
+                if let Some((handle, edge, _)) = app.rotation_handle() {
+                    let accent = egui::Color32::from_rgb(99, 102, 241);
+                    painter.line_segment([edge, handle], egui::Stroke::new(1.0, accent));
+                    painter.circle(
+                        handle,
+                        5.0,
+                        egui::Color32::WHITE,
+                        egui::Stroke::new(1.5, accent),
+                    );
+                }
+
+                // Lock badge on the hovered locked shape; clicking it unlocks
+                let idle = !app.is_dragging_shape && app.marquee_start.is_none();
+                if let Some(hover) = response.hover_pos().filter(|_| idle) {
+                    if let Some(badge) = app
+                        .hovered_locked_shape(hover)
+                        .and_then(|idx| app.lock_badge_pos(idx))
+                    {
+                        draw_lock_badge(&painter, badge, is_dark);
+                        if app.lock_badge_under_mouse(hover).is_some() {
+                            ctx.set_cursor_icon(egui::CursorIcon::PointingHand);
+                        }
+                    }
+                }
             }
 
             // Alignment guides
@@ -0,0 +1,22 @@
+use crate::app::App;
+use eframe::egui;
+
+pub fn render_edit_menu(app: &mut App, ui: &mut egui::Ui) {
+    ui.menu_button("Edit", |ui| {
+        if ui
+            .add_enabled(app.has_selection(), egui::Button::new("Lock Selection (Cmd+Shift+L)"))
+            .clicked()
+        {
+            ui.close();
+            app.lock_selection();
+        }
+        let any_locked = app.canvas.shapes.iter().any(|s| s.locked);
+        if ui
+            .add_enabled(any_locked, egui::Button::new("Unlock All"))
+            .clicked()
+        {
+            ui.close();
+            app.unlock_all();
+        }
+    });
+}
@@ -1,33 +1,198 @@
+use crate::app::export::ExportScope;
 use crate::app::App;
+use crate::export::tiles::check_pixel_limits;
+use crate::export::{sections_in_reading_order, ExportFormat};
 use eframe::egui;
 
+fn scope_label(scope: ExportScope, sections: &[(usize, egui::Rect)]) -> String {
+    match scope {
+        ExportScope::Board => "Whole board".to_string(),
+        ExportScope::Selection => "Selection".to_string(),
+        ExportScope::Section(id) => sections
+            .iter()
+            .position(|(section_id, _)| *section_id == id)
+            .map(|i| format!("Section {}", i + 1))
+            .unwrap_or_else(|| "Section".to_string()),
+        ExportScope::Viewport => "Current view".to_string(),
+    }
+}
+
 pub fn render_export_dialog(app: &mut App, ctx: &egui::Context) {
     if app.show_export_dialog {
+        let sections = sections_in_reading_order(&app.canvas.shapes);
+        if matches!(app.export_scope, ExportScope::Section(id) if !sections.iter().any(|(s, _)| *s == id))
+        {
+            app.export_scope = ExportScope::Board;
+        }
+
         egui::Window::new("Export Canvas")
             .collapsible(false)
             .resizable(false)
             .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
                 ui.vertical(|ui| {
-                    ui.label("Export the active canvas bounds to an image file:");
-                    ui.add(
-                        egui::Slider::new(&mut app.export_scale, 0.5..=4.0)
-                            .text("Resolution Scale"),
-                    );
+                    ui.label("Export the active canvas bounds to a file:");
+                    let settings = &mut app.export_settings;
                     ui.horizontal(|ui| {
-                        ui.radio_value(&mut app.export_jpeg, false, "PNG (Lossless)");
-                        ui.radio_value(&mut app.export_jpeg, true, "JPEG");
+                        ui.radio_value(&mut settings.format, ExportFormat::Png, "PNG (Lossless)");
+                        ui.radio_value(&mut settings.format, ExportFormat::Jpeg, "JPEG");
+                        ui.radio_value(&mut settings.format, ExportFormat::Webp, "WebP");
+                        ui.radio_value(&mut settings.format, ExportFormat::Svg, "SVG (Vector)");
+                        ui.radio_value(&mut settings.format, ExportFormat::Pdf, "PDF");
+                        ui.radio_value(&mut settings.format, ExportFormat::Html, "HTML");
                     });
-                    if app.export_jpeg {
+
+                    let has_selection = app.has_selection();
+                    egui::ComboBox::from_label("Scope")
+                        .selected_text(scope_label(app.export_scope, &sections))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut app.export_scope,
+                                ExportScope::Board,
+                                "Whole board",
+                            );
+                            ui.add_enabled_ui(has_selection, |ui| {
+                                ui.selectable_value(
+                                    &mut app.export_scope,
+                                    ExportScope::Selection,
+                                    "Selection",
+                                );
+                            });
+                            for (i, (id, _)) in sections.iter().enumerate() {
+                                ui.selectable_value(
+                                    &mut app.export_scope,
+                                    ExportScope::Section(*id),
+                                    format!("Section {}", i + 1),
+                                );
+                            }
+                            ui.selectable_value(
+                                &mut app.export_scope,
+                                ExportScope::Viewport,
+                                "Current view",
+                            );
+                        });
+                    let settings = &mut app.export_settings;
+                    if app.export_scope.uses_padding() {
                         ui.add(
-                            egui::Slider::new(&mut app.export_quality, 10..=100)
-                                .text("JPEG Quality"),
+                            egui::Slider::new(&mut settings.padding, 0.0..=200.0).text("Padding"),
                         );
                     }
+
+                    if !settings.format.is_vector() {
+                        ui.add(
+                            egui::Slider::new(&mut settings.scale, 0.5..=4.0)
+                                .text("Resolution Scale"),
+                        );
+                    }
+                    if settings.format == ExportFormat::Webp {
+                        ui.checkbox(&mut settings.webp_lossless, "Lossless");
+                    }
+                    if settings.format == ExportFormat::Jpeg
+                        || (settings.format == ExportFormat::Webp && !settings.webp_lossless)
+                    {
+                        ui.add(egui::Slider::new(&mut settings.quality, 10..=100).text("Quality"));
+                    }
+                    if settings.format.supports_transparency() {
+                        ui.checkbox(&mut settings.transparent, "Transparent background");
+                    }
+
+                    let format = app.export_settings.format;
+                    let (summary, too_large) = match app.export_target(ctx) {
+                        Ok(target) => match format {
+                            ExportFormat::Pdf => (
+                                format!(
+                                    "{} page(s), one per section, left to right, top to bottom",
+                                    target.pages.len()
+                                ),
+                                false,
+                            ),
+                            ExportFormat::Html => (
+                                format!(
+                                    "Interactive page with pan, zoom and {} section link(s)",
+                                    sections_in_reading_order(&target.shapes).len()
+                                ),
+                                false,
+                            ),
+                            ExportFormat::Svg => (
+                                format!(
+                                    "{} × {} units",
+                                    target.region.width().round(),
+                                    target.region.height().round()
+                                ),
+                                false,
+                            ),
+                            ExportFormat::Png | ExportFormat::Jpeg | ExportFormat::Webp => {
+                                let size = target.pixel_size();
+                                match check_pixel_limits(format, size) {
+                                    Ok(()) => (format!("{} × {} px", size[0], size[1]), false),
+                                    Err(e) => (e, true),
+                                }
+                            }
+                        },
+                        Err(e) => (e, false),
+                    };
+                    let color = if too_large {
+                        ui.visuals().error_fg_color
+                    } else {
+                        ui.visuals().weak_text_color()
+                    };
+                    ui.label(egui::RichText::new(summary).small().color(color));
+
+                    if let Some((done, total)) = app.export_progress {
+                        ui.add(
+                            egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                                .text(format!("Rendering tile {} of {}", done, total)),
+                        );
+                    } else if app.export_in_flight {
+                        ui.add(egui::ProgressBar::new(0.0).text("Exporting…"));
+                    }
+
+                    ui.separator();
+                    let copy_target = app.copy_target();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut app.export_settings.copy_scale, 0.5..=4.0)
+                                .text("Copy scale"),
+                        );
+                        if ui
+                            .add_enabled(copy_target.is_ok(), egui::Button::new("Copy as PNG"))
+                            .on_hover_text("Copy the selection to the clipboard (Cmd+Shift+C)")
+                            .clicked()
+                        {
+                            app.copy_selection_as_png(ctx);
+                        }
+                    });
+                    ui.checkbox(
+                        &mut app.export_settings.copy_transparent,
+                        "Transparent background when copying",
+                    );
+                    if has_selection {
+                        let (summary, color) = match &copy_target {
+                            Ok(target) => {
+                                let [width, height] = target.pixel_size();
+                                (format!("{} × {} px", width, height), ui.visuals().weak_text_color())
+                            }
+                            Err(e) => (e.clone(), ui.visuals().error_fg_color),
+                        };
+                        ui.label(egui::RichText::new(summary).small().color(color));
+                    }
+
                     ui.separator();
                     ui.horizontal(|ui| {
-                        if ui.button("Export to file").clicked() {
-                            app.export_file_dialog();
+                        let idle = !app.export_in_flight;
+                        if ui
+                            .add_enabled(idle && !too_large, egui::Button::new("Export to file"))
+                            .clicked()
+                        {
+                            app.export_file_dialog(ctx);
+                        }
+                        if !sections.is_empty()
+                            && ui
+                                .add_enabled(idle, egui::Button::new("Export each section..."))
+                                .on_hover_text("One file per section, named after its title")
+                                .clicked()
+                        {
+                            app.export_sections_dialog(ctx);
                         }
                         if ui.button("Cancel").clicked() {
                             app.show_export_dialog = false;
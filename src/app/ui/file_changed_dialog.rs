@@ -0,0 +1,54 @@
+use crate::app::App;
+use eframe::egui;
+
+pub fn render_file_changed_dialog(app: &mut App, ctx: &egui::Context) {
+    if !app.show_file_changed_dialog {
+        return;
+    }
+    let name = app
+        .current_file_path
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    egui::Window::new("Board Changed on Disk")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.vertical(|ui| {
+                ui.label(format!("{} was changed by another program.", name));
+                if app.is_dirty {
+                    ui.label(
+                        egui::RichText::new("You also have unsaved changes to this board.")
+                            .color(ui.visuals().warn_fg_color),
+                    );
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Reload")
+                        .on_hover_text("Open the version on disk and discard your changes")
+                        .clicked()
+                    {
+                        app.reload_from_disk(ctx);
+                    }
+                    if ui
+                        .button("Keep Mine")
+                        .on_hover_text("Keep your version; saving will overwrite the file")
+                        .clicked()
+                    {
+                        app.keep_local_version();
+                    }
+                    if ui
+                        .button("Merge")
+                        .on_hover_text("Add the shapes that only exist on disk to your board")
+                        .clicked()
+                    {
+                        app.merge_from_disk(ctx);
+                    }
+                });
+            });
+        });
+}
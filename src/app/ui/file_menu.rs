@@ -0,0 +1,66 @@
+use crate::app::recovery::describe_time;
+use crate::app::App;
+use crate::state::backup::list_backups;
+use eframe::egui;
+
+pub fn render_file_menu(app: &mut App, ui: &mut egui::Ui, ctx: &egui::Context) {
+    ui.menu_button("File", |ui| {
+        if ui.button("New Board (Cmd+N)").clicked() {
+            ui.close();
+            app.new_board(ctx);
+        }
+        if ui.button("Open... (Cmd+O)").clicked() {
+            ui.close();
+            app.open_file_dialog(ctx);
+        }
+        if ui.button("Open Recent... (Cmd+Shift+O)").clicked() {
+            ui.close();
+            app.show_recent_dialog = true;
+        }
+        if ui.button("Insert Board...").clicked() {
+            ui.close();
+            app.insert_board_dialog(ctx);
+        }
+        if ui.button("Save (Cmd+S)").clicked() {
+            ui.close();
+            app.save(ctx);
+        }
+        if ui.button("Save As...").clicked() {
+            ui.close();
+            app.save_file_dialog(ctx);
+        }
+
+        ui.separator();
+
+        if ui.button("Export... (Cmd+E)").clicked() {
+            ui.close();
+            app.show_export_dialog = true;
+        }
+        if ui.button("Export Sections...").clicked() {
+            ui.close();
+            app.export_sections_dialog(ctx);
+        }
+
+        ui.separator();
+
+        ui.add_enabled_ui(app.current_file_path.is_some(), |ui| {
+            ui.menu_button("Restore from Backup", |ui| {
+                let backups = app
+                    .current_file_path
+                    .as_deref()
+                    .map(list_backups)
+                    .unwrap_or_default();
+                if backups.is_empty() {
+                    ui.label("No backups yet");
+                }
+                for backup in &backups {
+                    let label = format!("Saved {}", describe_time(backup.modified));
+                    if ui.button(label).clicked() {
+                        ui.close();
+                        app.restore_backup(&backup.path, ctx);
+                    }
+                }
+            });
+        });
+    });
+}
@@ -1,24 +1,32 @@
 pub mod canvas_view;
+pub mod edit_menu;
 pub mod export_dialog;
+pub mod file_changed_dialog;
+pub mod file_menu;
+pub mod recent_dialog;
 pub mod text_editor;
 pub mod toast;
 pub mod toolbar;
 pub mod top_left;
 
 use crate::app::App;
-use crate::shapes::Tool;
 use canvas_view::render_central_canvas;
 use export_dialog::render_export_dialog;
+use file_changed_dialog::render_file_changed_dialog;
+use recent_dialog::render_recent_dialog;
 use text_editor::render_inline_text_editor;
 use toast::render_toast_notification;
 use toolbar::render_bottom_toolbar;
 use top_left::render_top_left_controls;
 use eframe::egui;
-use std::time::Instant;
 
 impl eframe::App for App {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, "top_panel_collapsed", &self.top_panel_collapsed);
+        self.remember_board_view();
+        eframe::set_value(storage, "recent_boards", &self.recent_boards);
+        eframe::set_value(storage, "restore_board_view", &self.restore_board_view);
+        eframe::set_value(storage, "export_settings", &self.export_settings);
         if let Some(path) = &self.current_file_path {
             eframe::set_value(
                 storage,
@@ -30,6 +38,8 @@ impl eframe::App for App {
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.apply_ui_events();
+        self.autosave_if_due(ctx);
+        self.check_external_changes(ctx);
 
         #[cfg(target_os = "macos")]
         for path in crate::macos_open::take_pending() {
@@ -42,7 +52,8 @@ impl eframe::App for App {
         if ctx.input(|i| i.viewport().close_requested()) {
             let empty_unsaved = self.canvas.shapes.is_empty() && self.current_file_path.is_none();
             if self.close_confirmed || !self.is_dirty || empty_unsaved {
-                // Allow close
+                // Allow close; nothing is left that needs recovering
+                self.discard_recovery();
             } else {
                 ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
 
@@ -54,7 +65,7 @@ impl eframe::App for App {
 
                 match confirm {
                     rfd::MessageDialogResult::Yes => {
-                        if self.save() {
+                        if self.save(ctx) {
                             self.close_confirmed = true;
                             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
@@ -95,24 +106,8 @@ impl eframe::App for App {
             });
 
         if has_paste {
-            if self.try_paste_clipboard_image(ctx) {
+            if self.try_paste_shapes(ctx) || self.try_paste_clipboard_image(ctx) {
                 self.is_dirty = true;
-            } else if let Some(mut shape) = self.copied_shape.clone() {
-                self.canvas.history.push(self.canvas.shapes.clone());
-                self.canvas.undo_history.clear();
-                self.is_dirty = true;
-
-                let target = self.paste_target_canvas(ctx);
-                let center = shape.data.get_bounds().center();
-                shape.data.translate(target - center);
-                shape.id = self.canvas.next_id;
-                self.canvas.next_id += 1;
-                shape.data.load_textures(ctx, shape.id);
-
-                self.canvas.shapes.push(shape);
-                self.select_single(self.canvas.shapes.len() - 1);
-                self.tool = Tool::Select;
-                self.notification = Some(("Pasted shape".to_string(), Instant::now()));
             } else {
                 self.paste_from_clipboard(ctx);
                 self.is_dirty = true;
@@ -176,6 +171,8 @@ impl eframe::App for App {
         render_central_canvas(self, ctx, is_dark);
         render_inline_text_editor(self, ctx);
         render_export_dialog(self, ctx);
+        render_recent_dialog(self, ctx);
+        render_file_changed_dialog(self, ctx);
         render_toast_notification(self, ctx);
     }
 }
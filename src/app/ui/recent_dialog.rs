@@ -0,0 +1,104 @@
+use crate::app::recovery::describe_age;
+use crate::app::App;
+use eframe::egui;
+
+const THUMBNAIL_BOX: egui::Vec2 = egui::vec2(96.0, 64.0);
+
+pub fn render_recent_dialog(app: &mut App, ctx: &egui::Context) {
+    if !app.show_recent_dialog {
+        return;
+    }
+    let mut open = true;
+    let mut to_open = None;
+    egui::Window::new("Recent Boards")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            if app.recent_boards.is_empty() {
+                ui.label("No boards opened yet.");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(420.0).show(ui, |ui| {
+                for entry in app.recent_boards.clone() {
+                    let exists = entry.path.is_file();
+                    let thumbnail = app.recent_thumbnail(&entry.path, ctx);
+                    let name = entry
+                        .path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    let folder = entry
+                        .path
+                        .parent()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default();
+
+                    let row = ui.add_enabled_ui(exists, |ui| {
+                        ui.horizontal(|ui| {
+                            let (rect, _) =
+                                ui.allocate_exact_size(THUMBNAIL_BOX, egui::Sense::hover());
+                            ui.painter().rect_filled(
+                                rect,
+                                egui::CornerRadius::same(4),
+                                ui.visuals().extreme_bg_color,
+                            );
+                            if let Some(tex) = &thumbnail {
+                                let size = tex.size_vec2();
+                                let fit = (THUMBNAIL_BOX.x / size.x).min(THUMBNAIL_BOX.y / size.y);
+                                let image_rect =
+                                    egui::Rect::from_center_size(rect.center(), size * fit);
+                                ui.painter().image(
+                                    tex.id(),
+                                    image_rect,
+                                    egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                                    egui::Color32::WHITE,
+                                );
+                            }
+                            ui.vertical(|ui| {
+                                ui.strong(&name);
+                                ui.label(
+                                    egui::RichText::new(&folder)
+                                        .small()
+                                        .color(ui.visuals().weak_text_color()),
+                                );
+                                ui.label(
+                                    egui::RichText::new(if exists {
+                                        format!("Opened {}", describe_age(entry.last_opened_time()))
+                                    } else {
+                                        "File not found".to_string()
+                                    })
+                                    .small()
+                                    .color(ui.visuals().weak_text_color()),
+                                );
+                            });
+                        })
+                        .response
+                    });
+                    let response = row.inner.interact(egui::Sense::click());
+                    if exists && response.clicked() {
+                        to_open = Some(entry.path.clone());
+                    }
+                    if exists && response.hovered() {
+                        ctx.set_cursor_icon(egui::CursorIcon::PointingHand);
+                    }
+                    ui.add_space(4.0);
+                }
+            });
+            ui.separator();
+            if ui.button("Clear List").clicked() {
+                app.recent_boards.clear();
+                app.recent_thumbnails.clear();
+            }
+        });
+    app.show_recent_dialog = open;
+
+    if let Some(path) = to_open {
+        if app.confirm_unsaved_changes("opening another board", ctx) {
+            app.show_recent_dialog = false;
+            app.open_kugel_file(&path, ctx);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::shapes::connector;
 use crate::shapes::{ShapeData, Tool};
 use eframe::egui;
 
@@ -24,6 +25,9 @@ pub fn render_inline_text_editor(app: &mut App, ctx: &egui::Context) {
                     };
                     (rect.min + egui::vec2(8.0, 8.0), *text_size, tc)
                 }
+                ShapeData::Connector {
+                    start, end, color, ..
+                } => (connector::label_pos(*start, *end), connector::LABEL_SIZE, *color),
                 _ => (egui::Pos2::ZERO, 24.0, egui::Color32::WHITE),
             };
             let screen_pos = app.canvas_to_screen(canvas_pos);
@@ -83,6 +87,9 @@ pub fn render_inline_text_editor(app: &mut App, ctx: &egui::Context) {
                         ShapeData::StickyNote { text, .. } => {
                             *text = app.editing_text_buffer.clone();
                         }
+                        ShapeData::Connector { label, .. } => {
+                            *label = app.editing_text_buffer.clone();
+                        }
                         _ => {}
                     }
                     app.check_and_spawn_title_preview_for_shape(idx, ctx);
@@ -107,6 +114,9 @@ pub fn render_inline_text_editor(app: &mut App, ctx: &egui::Context) {
                             ShapeData::StickyNote { text, .. } => {
                                 *text = app.editing_text_buffer.clone();
                             }
+                            ShapeData::Connector { label, .. } => {
+                                *label = app.editing_text_buffer.trim().to_string();
+                            }
                             _ => {}
                         }
                         app.is_dirty = true;
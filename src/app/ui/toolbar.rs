@@ -1,6 +1,7 @@
 use crate::app::App;
 use crate::icons::Icons;
-use crate::shapes::Tool;
+use crate::shapes::connector::ArrowHeads;
+use crate::shapes::{BlendMode, ShapeData, Tool};
 use eframe::egui;
 
 pub fn render_bottom_toolbar(
@@ -33,6 +34,7 @@ pub fn render_bottom_toolbar(
                             (Tool::Text, &icons.text, "Text (T)"),
                             (Tool::StickyNote, &icons.note, "Sticky Note (N)"),
                             (Tool::Section, &icons.section, "Section (F)"),
+                            (Tool::Connector, &icons.connector, "Connector (C)"),
                         ];
                         if compact_toolbar {
                             ui.spacing_mut().button_padding = egui::vec2(6.0, 6.0);
@@ -56,7 +58,7 @@ pub fn render_bottom_toolbar(
                                 .icon_button(ui, &icons.save, "Save Board (Cmd+S)")
                                 .clicked()
                             {
-                                app.save();
+                                app.save(ctx);
                             }
                             if icons
                                 .icon_button(ui, &icons.export, "Export Board (Cmd+E)")
@@ -116,6 +118,85 @@ pub fn render_bottom_toolbar(
                             app.recoloring_selection = false;
                         }
 
+                        let selected_style = app
+                            .primary_selected
+                            .and_then(|idx| app.canvas.shapes.get(idx))
+                            .map(|shape| (shape.opacity, shape.blend));
+                        if let Some((mut opacity, current_blend)) = selected_style {
+                            let opacity_resp = ui
+                                .add(
+                                    egui::Slider::new(&mut opacity, 0.05..=1.0)
+                                        .show_value(false),
+                                )
+                                .on_hover_text("Opacity");
+                            if opacity_resp.changed() {
+                                if !app.changing_opacity {
+                                    app.canvas.push_history();
+                                    app.changing_opacity = true;
+                                }
+                                for &idx in &app.selected_shape_indices {
+                                    if let Some(shape) = app.canvas.shapes.get_mut(idx) {
+                                        shape.opacity = opacity;
+                                    }
+                                }
+                                app.is_dirty = true;
+                            }
+                            if app.changing_opacity && !opacity_resp.dragged() {
+                                app.changing_opacity = false;
+                            }
+
+                            let mut blend = current_blend;
+                            egui::ComboBox::from_id_salt("blend_mode")
+                                .selected_text(blend.label())
+                                .show_ui(ui, |ui| {
+                                    for option in BlendMode::ALL {
+                                        ui.selectable_value(&mut blend, option, option.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Blend Mode");
+                            if blend != current_blend {
+                                app.canvas.push_history();
+                                for &idx in &app.selected_shape_indices {
+                                    if let Some(shape) = app.canvas.shapes.get_mut(idx) {
+                                        shape.blend = blend;
+                                    }
+                                }
+                                app.is_dirty = true;
+                            }
+                        }
+
+                        let selected_arrows = app
+                            .selected_shape_indices
+                            .iter()
+                            .find_map(|&idx| match app.canvas.shapes.get(idx).map(|s| &s.data) {
+                                Some(ShapeData::Connector { arrows, .. }) => Some(*arrows),
+                                _ => None,
+                            });
+                        if let Some(current) = selected_arrows {
+                            let mut chosen = current;
+                            egui::ComboBox::from_id_salt("connector_arrows")
+                                .selected_text(chosen.label())
+                                .show_ui(ui, |ui| {
+                                    for option in ArrowHeads::ALL {
+                                        ui.selectable_value(&mut chosen, option, option.label());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Arrowheads");
+                            if chosen != current {
+                                app.canvas.push_history();
+                                for &idx in &app.selected_shape_indices {
+                                    if let Some(ShapeData::Connector { arrows, .. }) =
+                                        app.canvas.shapes.get_mut(idx).map(|s| &mut s.data)
+                                    {
+                                        *arrows = chosen;
+                                    }
+                                }
+                                app.is_dirty = true;
+                            }
+                        }
+
                         ui.separator();
 
                         if icons.icon_button(ui, &icons.undo, "Undo (Cmd+Z)").clicked() {
@@ -146,7 +227,7 @@ pub fn render_bottom_toolbar(
                             .icon_button(ui, &icons.save, "Save Board (Cmd+S)")
                             .clicked()
                         {
-                            app.save();
+                            app.save(ctx);
                         }
                         if icons
                             .icon_button(ui, &icons.open, "Open Board (Cmd+O)")
@@ -1,3 +1,5 @@
+use crate::app::ui::edit_menu::render_edit_menu;
+use crate::app::ui::file_menu::render_file_menu;
 use crate::app::App;
 use crate::icons::Icons;
 use crate::updater::UpdateState;
@@ -36,6 +38,8 @@ pub fn render_top_left_controls(
                             {
                                 app.top_panel_collapsed = !app.top_panel_collapsed;
                             }
+                            render_file_menu(app, ui, ctx);
+                            render_edit_menu(app, ui);
                         });
                         if app.top_panel_collapsed {
                             return;
@@ -56,6 +60,8 @@ pub fn render_top_left_controls(
                             );
                         });
                         ui.checkbox(&mut app.use_grid, "Show Grid");
+                        ui.checkbox(&mut app.restore_board_view, "Restore Last View per Board")
+                            .on_hover_text("Reopen boards at the zoom and position you left them in");
                         ui.horizontal(|ui| {
                             let theme_icon = if app.dark_mode {
                                 &icons.theme_light
@@ -0,0 +1,137 @@
+use crate::shapes::ShapeData;
+use eframe::egui;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Hex-encoded SHA-256 of `bytes`, the key images are stored under.
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Board-level image store keyed by content hash. Image shapes keep the hash
+/// plus a shared handle to the bytes, so duplicates, clipboard copies and undo
+/// snapshots never copy the encoded image or upload a second texture.
+#[derive(Default)]
+pub struct AssetStore {
+    images: HashMap<String, Arc<[u8]>>,
+    textures: HashMap<String, egui::TextureHandle>,
+}
+
+impl AssetStore {
+    /// Point an image shape at the stored copy of its bytes, adding them to
+    /// the store if this content has not been seen yet. Other shapes are left untouched.
+    pub fn intern(&mut self, data: &mut ShapeData) {
+        let ShapeData::Image { bytes, asset, .. } = data else {
+            return;
+        };
+        let hash = match asset {
+            Some(h) => h.clone(),
+            None => content_hash(bytes),
+        };
+        let shared = self
+            .images
+            .entry(hash.clone())
+            .or_insert_with(|| bytes.clone());
+        *bytes = shared.clone();
+        *asset = Some(hash);
+    }
+
+    /// Attach the shared texture for an image shape, decoding and uploading it
+    /// only the first time its content is seen.
+    pub fn load_texture(&mut self, data: &mut ShapeData, ctx: &egui::Context) {
+        self.intern(data);
+        let ShapeData::Image {
+            bytes,
+            asset: Some(hash),
+            texture,
+            ..
+        } = data
+        else {
+            return;
+        };
+        if texture.is_some() {
+            return;
+        }
+        if let Some(tex) = self.textures.get(hash.as_str()) {
+            *texture = Some(tex.clone());
+            return;
+        }
+        if let Ok(img) = image::load_from_memory(bytes) {
+            let rgba = img.to_rgba8();
+            let color_img = egui::ColorImage::from_rgba_unmultiplied(
+                [rgba.width() as usize, rgba.height() as usize],
+                &rgba.into_raw(),
+            );
+            let tex = ctx.load_texture(
+                format!("image_texture_{}", hash),
+                color_img,
+                egui::TextureOptions {
+                    magnification: egui::TextureFilter::Linear,
+                    minification: egui::TextureFilter::Linear,
+                    mipmap_mode: Some(egui::TextureFilter::Linear),
+                    wrap_mode: egui::TextureWrapMode::ClampToEdge,
+                },
+            );
+            self.textures.insert(hash.clone(), tex.clone());
+            *texture = Some(tex);
+        }
+    }
+
+    /// Drop images that no shape (on the canvas or in undo history) uses any more.
+    pub fn prune(&mut self) {
+        self.images.retain(|_, bytes| Arc::strong_count(bytes) > 1);
+        let images = &self.images;
+        self.textures.retain(|hash, _| images.contains_key(hash));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Shape;
+
+    fn image(bytes: Vec<u8>) -> Shape {
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(10.0, 10.0));
+        Shape::new_image(0, rect, bytes.into(), [10.0, 10.0], None)
+    }
+
+    fn image_bytes(shape: &Shape) -> Arc<[u8]> {
+        match &shape.data {
+            ShapeData::Image { bytes, .. } => bytes.clone(),
+            _ => panic!("Expected Image"),
+        }
+    }
+
+    #[test]
+    fn test_intern_shares_identical_images() {
+        let mut store = AssetStore::default();
+        let mut a = image(vec![1, 2, 3]);
+        let mut b = image(vec![1, 2, 3]);
+        let mut c = image(vec![4, 5]);
+        store.intern(&mut a.data);
+        store.intern(&mut b.data);
+        store.intern(&mut c.data);
+
+        assert_eq!(store.images.len(), 2);
+        assert!(Arc::ptr_eq(&image_bytes(&a), &image_bytes(&b)));
+        assert!(!Arc::ptr_eq(&image_bytes(&a), &image_bytes(&c)));
+        assert!(matches!(&a.data, ShapeData::Image { asset: Some(h), .. } if *h == content_hash(&[1, 2, 3])));
+    }
+
+    #[test]
+    fn test_prune_drops_unused_images() {
+        let mut store = AssetStore::default();
+        let mut a = image(vec![1, 2, 3]);
+        let mut b = image(vec![4, 5]);
+        store.intern(&mut a.data);
+        store.intern(&mut b.data);
+        drop(b);
+        store.prune();
+        assert_eq!(store.images.len(), 1);
+        assert!(store.images.contains_key(&content_hash(&[1, 2, 3])));
+    }
+}
@@ -1,9 +1,13 @@
+use crate::assets::AssetStore;
+use crate::shapes::connector;
 use crate::shapes::{Shape, ShapeData, Tool};
+use std::collections::HashMap;
 use eframe::egui;
 
 #[derive(Default)]
 pub struct Canvas {
     pub shapes: Vec<Shape>,
+    pub assets: AssetStore,
     pub current_shape: Option<Shape>,
     pub history: Vec<Vec<Shape>>,
     pub undo_history: Vec<Vec<Shape>>,
@@ -81,6 +85,10 @@ impl Canvas {
                 ));
                 None
             }
+            Tool::Connector => {
+                self.current_shape = Some(Shape::new_connector(self.next_id, pos, pos, color, width));
+                None
+            }
             Tool::Text => {
                 // Text is created instantly and placed in edit mode
                 let text_shape = Shape::new_text(
@@ -135,7 +143,7 @@ impl Canvas {
                         *rect = egui::Rect::from_two_pos(start, pos);
                     }
                 }
-                ShapeData::Line { end, .. } => {
+                ShapeData::Line { end, .. } | ShapeData::Connector { end, .. } => {
                     *end = pos;
                 }
                 ShapeData::Circle { center, radius, .. } => {
@@ -153,11 +161,13 @@ impl Canvas {
 
     pub fn finish_shape(&mut self) -> Option<usize> {
         self.creation_start_pos = None;
-        if let Some(shape) = self.current_shape.take() {
+        if let Some(mut shape) = self.current_shape.take() {
             // Verify shape has substance (e.g. pen has points)
             let keep = match &shape.data {
                 ShapeData::Pen { points, .. } => points.len() > 1,
-                ShapeData::Line { start, end, .. } => start.distance(*end) > 2.0,
+                ShapeData::Line { start, end, .. } | ShapeData::Connector { start, end, .. } => {
+                    start.distance(*end) > 2.0
+                }
                 ShapeData::Rectangle { rect, .. } => rect.width() > 1.0 || rect.height() > 1.0,
                 ShapeData::Circle { radius, .. } => *radius > 1.0,
                 ShapeData::SectionBox { rect, .. } => rect.width() > 5.0 && rect.height() > 5.0,
@@ -165,15 +175,41 @@ impl Canvas {
             };
 
             if keep {
+                if let ShapeData::Connector { start, end, start_id, end_id, .. } = &mut shape.data {
+                    *start_id = self.connector_target_at(*start);
+                    *end_id = self.connector_target_at(*end);
+                }
                 self.history.push(self.shapes.clone());
                 self.shapes.push(shape);
                 self.next_id += 1;
+                self.route_connectors();
                 return Some(self.shapes.len() - 1);
             }
         }
         None
     }
 
+    /// Id of the shape a connector end dropped at `pos` attaches to: the
+    /// topmost shape there, with sections only as a last resort.
+    fn connector_target_at(&self, pos: egui::Pos2) -> Option<usize> {
+        let candidates = || {
+            self.shapes
+                .iter()
+                .rev()
+                .filter(|shape| !matches!(shape.data, ShapeData::Connector { .. }))
+                .filter(|shape| shape.contains_point(pos, 5.0))
+        };
+        candidates()
+            .find(|shape| !matches!(shape.data, ShapeData::SectionBox { .. }))
+            .or_else(|| candidates().next())
+            .map(|shape| shape.id)
+    }
+
+    /// Keep attached connector ends on the outline of their shapes.
+    pub fn route_connectors(&mut self) {
+        connector::route_connectors(&mut self.shapes);
+    }
+
     pub fn add_image(&mut self, pos: egui::Pos2, bytes: Vec<u8>, size: [f32; 2], ctx: &egui::Context) -> usize {
         self.history.push(self.shapes.clone());
         self.undo_history.clear();
@@ -181,8 +217,8 @@ impl Canvas {
         let w = size[0];
         let h = size[1];
         let rect = egui::Rect::from_center_size(pos, egui::vec2(w, h));
-        let mut shape = Shape::new_image(self.next_id, rect, bytes, size, None);
-        shape.data.load_textures(ctx, self.next_id);
+        let mut shape = Shape::new_image(self.next_id, rect, bytes.into(), size, None);
+        self.assets.load_texture(&mut shape.data, ctx);
 
         let added_idx = self.shapes.len();
         self.shapes.push(shape);
@@ -213,7 +249,7 @@ impl Canvas {
         let clip = painter.clip_rect();
 
         let screen_bounds_for = |shape: &Shape| -> egui::Rect {
-            let bounds = shape.data.get_bounds();
+            let bounds = shape.get_bounds();
             egui::Rect::from_min_max(
                 egui::pos2(bounds.min.x * zoom + pan_offset.x, bounds.min.y * zoom + pan_offset.y),
                 egui::pos2(bounds.max.x * zoom + pan_offset.x, bounds.max.y * zoom + pan_offset.y),
@@ -226,11 +262,11 @@ impl Canvas {
             if !matches!(shape.data, ShapeData::SectionBox { .. }) {
                 continue;
             }
-            let bounds = shape.data.get_bounds();
+            let bounds = shape.get_bounds();
             if bounds.is_positive() && !clip.intersects(screen_bounds_for(shape)) {
                 continue;
             }
-            shape.data.render(painter, zoom, pan_offset, false);
+            shape.render(painter, zoom, pan_offset, false);
         }
 
         // Second pass: everything else
@@ -238,22 +274,84 @@ impl Canvas {
             if matches!(shape.data, ShapeData::SectionBox { .. }) {
                 continue;
             }
-            let bounds = shape.data.get_bounds();
+            let bounds = shape.get_bounds();
             if bounds.is_positive() && !clip.intersects(screen_bounds_for(shape)) {
                 continue;
             }
             let is_editing = Some(idx) == editing_index;
-            shape.data.render(painter, zoom, pan_offset, is_editing);
+            shape.render(painter, zoom, pan_offset, is_editing);
         }
 
         if let Some(shape) = &self.current_shape {
-            shape.data.render(painter, zoom, pan_offset, false);
+            shape.render(painter, zoom, pan_offset, false);
         }
     }
 
     pub fn load_textures(&mut self, ctx: &egui::Context) {
         for shape in &mut self.shapes {
-            shape.data.load_textures(ctx, shape.id);
+            self.assets.load_texture(&mut shape.data, ctx);
+        }
+    }
+
+    /// Add shapes from another board centered on `target`, with fresh ids,
+    /// as a single undo step. Returns the indices of the inserted shapes.
+    pub fn insert_shapes(
+        &mut self,
+        mut shapes: Vec<Shape>,
+        target: egui::Pos2,
+        ctx: &egui::Context,
+    ) -> std::ops::Range<usize> {
+        let Some(bounds) = shapes
+            .iter()
+            .map(|s| s.get_bounds())
+            .reduce(|a, b| a.union(b))
+        else {
+            return self.shapes.len()..self.shapes.len();
+        };
+        let offset = target - bounds.center();
+        for shape in &mut shapes {
+            shape.data.translate(offset);
+        }
+        self.append_shapes(shapes, ctx)
+    }
+
+    /// Add shapes where they are, with fresh ids, as a single undo step.
+    /// Returns the indices of the added shapes.
+    pub fn append_shapes(
+        &mut self,
+        mut shapes: Vec<Shape>,
+        ctx: &egui::Context,
+    ) -> std::ops::Range<usize> {
+        let start = self.shapes.len();
+        if shapes.is_empty() {
+            return start..start;
+        }
+        self.push_history();
+        let mut ids = HashMap::new();
+        for shape in &mut shapes {
+            ids.insert(shape.id, self.next_id);
+            shape.id = self.next_id;
+            self.next_id += 1;
+            self.assets.load_texture(&mut shape.data, ctx);
+        }
+        connector::remap_connector_ids(&mut shapes, &ids);
+        self.regroup(&mut shapes);
+        self.shapes.extend(shapes);
+        start..self.shapes.len()
+    }
+
+    /// Give the groups among `shapes` fresh ids, so copies of a group form a
+    /// group of their own instead of joining the original.
+    pub fn regroup(&mut self, shapes: &mut [Shape]) {
+        let mut groups = HashMap::new();
+        for shape in shapes {
+            if let Some(group) = shape.group {
+                let new_group = *groups.entry(group).or_insert_with(|| {
+                    self.next_id += 1;
+                    self.next_id - 1
+                });
+                shape.group = Some(new_group);
+            }
         }
     }
 
@@ -313,5 +411,80 @@ mod tests {
         canvas.undo();
         assert_eq!(canvas.shapes.len(), 1);
     }
-}
 
+    #[test]
+    fn test_duplicate_images_share_bytes() {
+        let mut canvas = Canvas::default();
+        let ctx = egui::Context::default();
+        canvas.add_image(egui::pos2(0.0, 0.0), vec![1, 2, 3], [10.0, 10.0], &ctx);
+        canvas.add_image(egui::pos2(50.0, 0.0), vec![1, 2, 3], [10.0, 10.0], &ctx);
+
+        let bytes: Vec<_> = canvas
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.data {
+                ShapeData::Image { bytes, .. } => Some(bytes.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(bytes.len(), 2);
+        assert!(std::sync::Arc::ptr_eq(&bytes[0], &bytes[1]));
+    }
+
+    #[test]
+    fn test_insert_shapes_reassigns_ids_and_undoes_as_one_step() {
+        let mut canvas = Canvas::default();
+        let ctx = egui::Context::default();
+        canvas.add_text(egui::pos2(0.0, 0.0), "Existing".to_string(), egui::Color32::WHITE);
+
+        let mut other = Canvas::default();
+        other.add_text(egui::pos2(0.0, 0.0), "A".to_string(), egui::Color32::WHITE);
+        other.add_text(egui::pos2(100.0, 0.0), "B".to_string(), egui::Color32::WHITE);
+        let incoming = other.shapes.clone();
+        let incoming_center = incoming
+            .iter()
+            .map(|s| s.get_bounds())
+            .reduce(|a, b| a.union(b))
+            .unwrap()
+            .center();
+
+        let range = canvas.insert_shapes(incoming, egui::pos2(500.0, 500.0), &ctx);
+        assert_eq!(range, 1..3);
+        let ids: Vec<usize> = canvas.shapes.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(canvas.next_id, 3);
+        let moved = canvas.shapes[1].data.get_bounds().center() - other.shapes[0].data.get_bounds().center();
+        assert_eq!(moved, egui::pos2(500.0, 500.0) - incoming_center);
+
+        canvas.undo();
+        assert_eq!(canvas.shapes.len(), 1);
+    }
+
+    #[test]
+    fn test_connector_binds_to_shapes_under_its_ends() {
+        let mut canvas = Canvas::default();
+        let ctx = egui::Context::default();
+        for x in [0.0, 300.0] {
+            canvas.start_shape(Tool::FilledRectangle, egui::pos2(x, 0.0), egui::Color32::RED, 2.0, true);
+            canvas.update_current_shape(egui::pos2(x + 100.0, 50.0));
+            canvas.finish_shape();
+        }
+        canvas.start_shape(Tool::Connector, egui::pos2(50.0, 25.0), egui::Color32::WHITE, 2.0, false);
+        canvas.update_current_shape(egui::pos2(350.0, 25.0));
+        let idx = canvas.finish_shape().unwrap();
+        let ShapeData::Connector { start, end, start_id, end_id, .. } = canvas.shapes[idx].data else {
+            panic!("expected a connector");
+        };
+        assert_eq!((start_id, end_id), (Some(0), Some(1)));
+        assert_eq!(start, egui::pos2(104.0, 25.0));
+        assert_eq!(end, egui::pos2(296.0, 25.0));
+
+        // Copies point at the copied shapes, not the originals
+        let copies = canvas.shapes.clone();
+        let range = canvas.append_shapes(copies, &ctx);
+        let ShapeData::Connector { start_id, end_id, .. } = canvas.shapes[range.end - 1].data else {
+            panic!("expected a connector");
+        };
+        assert_eq!((start_id, end_id), (Some(3), Some(4)));
+    }
+}
@@ -0,0 +1,219 @@
+//! Headless command line: `kugel export` renders a board without opening a window.
+
+use crate::export::{
+    section_title, sections_in_reading_order, write_target, ExportFormat, ExportSettings,
+    ExportTarget,
+};
+use crate::shapes::connector::route_connectors;
+use crate::state::container::load_board;
+use eframe::egui;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage:
+  kugel [BOARD.kugel]              Open the app, optionally with a board
+  kugel export BOARD.kugel -o OUT  Export a board without opening a window
+
+Export options:
+  -o, --output PATH       Output file; the format follows its extension
+                          (png, jpg, webp, svg, pdf, html)
+      --scale N           Pixels per canvas unit for raster formats (default 2)
+      --section NAME      Export only this section, by title or 1-based number
+      --padding N         Canvas units around the content (default 50)
+      --quality N         JPEG and lossy WebP quality, 1-100 (default 90)
+      --lossless          Lossless WebP
+      --transparent       Leave out the background (PNG and WebP)
+  -h, --help              Show this help";
+
+pub enum Command {
+    Help,
+    Export(ExportArgs),
+}
+
+pub struct ExportArgs {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub section: Option<String>,
+    pub settings: ExportSettings,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<&String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+/// Parse the arguments after the program name. `Ok(None)` means the app
+/// should start normally.
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    match args.first().map(String::as_str) {
+        Some("-h" | "--help") => return Ok(Some(Command::Help)),
+        Some("export") => {}
+        _ => return Ok(None),
+    }
+
+    let mut input = None;
+    let mut output = None;
+    let mut section = None;
+    let mut settings = ExportSettings::default();
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Some(Command::Help)),
+            "-o" | "--output" => {
+                output = Some(PathBuf::from(
+                    iter.next()
+                        .ok_or_else(|| format!("{} needs a value", arg))?,
+                ))
+            }
+            "--section" => {
+                section = Some(
+                    iter.next()
+                        .ok_or_else(|| format!("{} needs a value", arg))?
+                        .clone(),
+                )
+            }
+            "--scale" => settings.scale = parse_number(arg, iter.next())?,
+            "--padding" => settings.padding = parse_number(arg, iter.next())?,
+            "--quality" => settings.quality = parse_number(arg, iter.next())?,
+            "--lossless" => settings.webp_lossless = true,
+            "--transparent" => settings.transparent = true,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+
+    let input = input.ok_or("Missing the board to export")?;
+    let output = output.ok_or("Missing the output file (-o)")?;
+    let ext = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    settings.format = ExportFormat::from_extension(ext)
+        .ok_or_else(|| format!("Unsupported output format: {}", output.display()))?;
+    if settings.scale.is_nan() || settings.scale <= 0.0 {
+        return Err("--scale must be greater than 0".to_string());
+    }
+    if settings.padding.is_nan() || settings.padding < 0.0 {
+        return Err("--padding must be 0 or more".to_string());
+    }
+    if !(1..=100).contains(&settings.quality) {
+        return Err("--quality must be between 1 and 100".to_string());
+    }
+
+    Ok(Some(Command::Export(ExportArgs {
+        input,
+        output,
+        section,
+        settings,
+    })))
+}
+
+pub fn export(args: &ExportArgs) -> Result<(), String> {
+    let state = load_board(&args.input).map_err(|e| e.to_string())?;
+    let [r, g, b, a] = state.background_color;
+    let bg = egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+    let mut shapes = state.shapes;
+    route_connectors(&mut shapes);
+
+    let target = match &args.section {
+        None => ExportTarget::content(shapes, args.settings.padding, args.settings.scale)?,
+        Some(wanted) => {
+            let sections = sections_in_reading_order(&shapes);
+            let by_number = wanted
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| sections.get(i));
+            let rect = by_number
+                .or_else(|| {
+                    sections.iter().find(|(_, rect)| {
+                        section_title(&shapes, *rect)
+                            .is_some_and(|title| title.eq_ignore_ascii_case(wanted))
+                    })
+                })
+                .map(|(_, rect)| *rect)
+                .ok_or_else(|| format!("No section named \"{}\"", wanted))?;
+            ExportTarget::clipped(&shapes, rect, args.settings.scale)
+        }
+    };
+
+    write_target(&target, &args.settings, bg, &args.output, &mut |_, _| {})
+}
+
+/// Run a command line request and return the process exit code.
+pub fn run(command: Command) -> i32 {
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            0
+        }
+        Command::Export(args) => match export(&args) {
+            Ok(()) => {
+                println!("Exported {}", args.output.display());
+                0
+            }
+            Err(e) => {
+                eprintln!("kugel: {}", e);
+                1
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_export_args() {
+        let parsed = parse_args(&args(&[
+            "export",
+            "board.kugel",
+            "-o",
+            "out.webp",
+            "--scale",
+            "3",
+            "--section",
+            "Moodboard A",
+            "--lossless",
+        ]))
+        .unwrap();
+        let Some(Command::Export(export)) = parsed else {
+            panic!("expected an export command");
+        };
+        assert_eq!(export.input, PathBuf::from("board.kugel"));
+        assert_eq!(export.output, PathBuf::from("out.webp"));
+        assert_eq!(export.section.as_deref(), Some("Moodboard A"));
+        assert!(export.settings.format == ExportFormat::Webp);
+        assert_eq!(export.settings.scale, 3.0);
+        assert!(export.settings.webp_lossless);
+    }
+
+    #[test]
+    fn test_parse_args_gui_and_errors() {
+        assert!(parse_args(&args(&[])).unwrap().is_none());
+        assert!(parse_args(&args(&["board.kugel"])).unwrap().is_none());
+        assert!(parse_args(&args(&["export", "board.kugel"])).is_err());
+        assert!(parse_args(&args(&["export", "board.kugel", "-o", "out.gif"])).is_err());
+        assert!(parse_args(&args(&["export", "b.kugel", "-o", "o.png", "--scale", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_rejects_out_of_range_numbers() {
+        let jpeg = |flag: &str, value: &str| {
+            parse_args(&args(&["export", "b.kugel", "-o", "o.jpg", flag, value]))
+        };
+        assert!(jpeg("--padding", "-1000").is_err());
+        assert!(jpeg("--padding", "0").is_ok());
+        assert!(jpeg("--quality", "0").is_err());
+        assert!(jpeg("--quality", "101").is_err());
+        assert!(jpeg("--quality", "100").is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::export::svg::{escape, num, svg_element};
+use crate::export::{section_title, sections_in_reading_order};
+use crate::shapes::Shape;
+use eframe::egui;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Page script: drag to pan, wheel or pinch to zoom, and section buttons
+/// that fly to their rect. Everything works on the SVG viewBox.
+const VIEWER_SCRIPT: &str = r#"
+const svg = document.querySelector('svg');
+const home = svg.viewBox.baseVal;
+let view = { x: home.x, y: home.y, w: home.width, h: home.height };
+function apply() {
+  const r = svg.getBoundingClientRect();
+  const aspect = r.width / r.height;
+  let { x, y, w, h } = view;
+  if (w / h < aspect) { const nw = h * aspect; x -= (nw - w) / 2; w = nw; }
+  else { const nh = w / aspect; y -= (nh - h) / 2; h = nh; }
+  view = { x, y, w, h };
+  svg.setAttribute('viewBox', `${x} ${y} ${w} ${h}`);
+}
+function show(x, y, w, h) { view = { x, y, w, h }; apply(); }
+function toCanvas(e) {
+  const r = svg.getBoundingClientRect();
+  return { x: view.x + (e.clientX - r.left) / r.width * view.w,
+           y: view.y + (e.clientY - r.top) / r.height * view.h };
+}
+let drag = null;
+svg.addEventListener('pointerdown', e => {
+  if (e.target.closest('a')) return;
+  drag = { x: e.clientX, y: e.clientY };
+  svg.setPointerCapture(e.pointerId);
+});
+svg.addEventListener('pointermove', e => {
+  if (!drag) return;
+  const r = svg.getBoundingClientRect();
+  view.x -= (e.clientX - drag.x) / r.width * view.w;
+  view.y -= (e.clientY - drag.y) / r.height * view.h;
+  drag = { x: e.clientX, y: e.clientY };
+  apply();
+});
+svg.addEventListener('pointerup', () => { drag = null; });
+svg.addEventListener('wheel', e => {
+  e.preventDefault();
+  const p = toCanvas(e);
+  const f = Math.exp(e.deltaY * (e.ctrlKey ? 0.01 : 0.002));
+  show(p.x - (p.x - view.x) * f, p.y - (p.y - view.y) * f, view.w * f, view.h * f);
+}, { passive: false });
+document.querySelectorAll('[data-view]').forEach(b => b.addEventListener('click', () => {
+  const [x, y, w, h] = b.dataset.view.split(' ').map(Number);
+  show(x, y, w, h);
+}));
+window.addEventListener('resize', apply);
+apply();
+"#;
+
+const STYLE: &str = r#"
+html, body { margin: 0; height: 100%; overflow: hidden; font-family: 'Open Sans', sans-serif; }
+svg { display: block; width: 100vw; height: 100vh; cursor: grab; touch-action: none; }
+svg:active { cursor: grabbing; }
+svg a { cursor: pointer; }
+nav { position: fixed; top: 12px; left: 12px; display: flex; flex-wrap: wrap; gap: 6px; max-width: 90vw; }
+nav button { border: 1px solid rgb(75,85,99); background: rgb(31,41,55); color: white;
+  border-radius: 14px; padding: 4px 12px; font: inherit; font-size: 13px; cursor: pointer; }
+nav button:hover { background: rgb(55,65,81); }
+"#;
+
+pub fn export_canvas_to_html(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    region: egui::Rect,
+    export_path: &Path,
+) -> Result<(), String> {
+    let title = export_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Kugel Board".to_string());
+    let html = render_html(shapes, bg_color, region, &title);
+    std::fs::write(export_path, html).map_err(|e| format!("Failed to write export file: {}", e))
+}
+
+/// Single-file web page showing the board as inline SVG with images
+/// embedded, pan and zoom, clickable links, and a button per section.
+pub fn render_html(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    region: egui::Rect,
+    title: &str,
+) -> String {
+    let [r, g, b, _] = bg_color.to_srgba_unmultiplied();
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, r#"<html lang="en"><head><meta charset="utf-8">"#);
+    let _ = writeln!(
+        out,
+        r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#
+    );
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(
+        out,
+        "<style>{}body {{ background: rgb({},{},{}); }}</style>",
+        STYLE, r, g, b
+    );
+    let _ = writeln!(out, "</head><body>");
+    out.push_str(&svg_element(shapes, bg_color, region));
+
+    let sections = sections_in_reading_order(shapes);
+    let _ = writeln!(out, "<nav>");
+    let _ = writeln!(
+        out,
+        r#"<button data-view="{} {} {} {}">Whole board</button>"#,
+        num(region.min.x),
+        num(region.min.y),
+        num(region.width()),
+        num(region.height())
+    );
+    for (i, (_, rect)) in sections.iter().enumerate() {
+        let label = section_title(shapes, *rect).unwrap_or_else(|| format!("Section {}", i + 1));
+        let view = rect.expand(20.0);
+        let _ = writeln!(
+            out,
+            r#"<button data-view="{} {} {} {}">{}</button>"#,
+            num(view.min.x),
+            num(view.min.y),
+            num(view.width()),
+            num(view.height()),
+            escape(&label)
+        );
+    }
+    let _ = writeln!(out, "</nav>");
+    let _ = writeln!(out, "<script>{}</script>", VIEWER_SCRIPT);
+    let _ = writeln!(out, "</body></html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_has_board_and_section_navigation() {
+        let shapes = vec![
+            Shape::new_section(
+                0,
+                egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(200.0, 100.0)),
+                egui::Color32::WHITE,
+            ),
+            Shape::new_text(
+                1,
+                egui::pos2(10.0, 10.0),
+                "Mood <A>".to_string(),
+                egui::Color32::WHITE,
+                16.0,
+            ),
+        ];
+        let region = egui::Rect::from_min_size(egui::pos2(-50.0, -50.0), egui::vec2(300.0, 200.0));
+        let html = render_html(&shapes, egui::Color32::BLACK, region, "Board & Co");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Board &amp; Co</title>"));
+        assert!(html.contains(r#"viewBox="-50 -50 300 200""#));
+        assert!(html.contains(r#"<button data-view="-20 -20 240 140">Mood &lt;A&gt;</button>"#));
+        assert!(html.contains("<script>"));
+    }
+}
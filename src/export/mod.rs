@@ -0,0 +1,318 @@
+pub mod html;
+pub mod pdf;
+pub mod raster;
+pub mod svg;
+mod text;
+pub mod tiles;
+
+use crate::shapes::{Shape, ShapeData};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub use html::export_canvas_to_html;
+pub use pdf::export_canvas_to_pdf;
+pub use raster::{export_canvas_to_image, render_rgba, render_thumbnail};
+pub use svg::export_canvas_to_svg;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Svg,
+    Pdf,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg => "jpg",
+            ExportFormat::Webp => "webp",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    /// Format for a file extension, case-insensitive.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "png" => Some(ExportFormat::Png),
+            "jpg" | "jpeg" => Some(ExportFormat::Jpeg),
+            "webp" => Some(ExportFormat::Webp),
+            "svg" => Some(ExportFormat::Svg),
+            "pdf" => Some(ExportFormat::Pdf),
+            "html" | "htm" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    /// Vector formats ignore the resolution scale.
+    pub fn is_vector(self) -> bool {
+        matches!(
+            self,
+            ExportFormat::Svg | ExportFormat::Pdf | ExportFormat::Html
+        )
+    }
+
+    /// Raster formats with an alpha channel.
+    pub fn supports_transparency(self) -> bool {
+        matches!(self, ExportFormat::Png | ExportFormat::Webp)
+    }
+
+    pub fn filter_name(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "PNG Image",
+            ExportFormat::Jpeg => "JPEG Image",
+            ExportFormat::Webp => "WebP Image",
+            ExportFormat::Svg => "SVG Vector Image",
+            ExportFormat::Pdf => "PDF Document",
+            ExportFormat::Html => "Web Page",
+        }
+    }
+}
+
+/// Export options chosen in the export dialog, remembered across sessions.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    /// Pixels per canvas unit for raster formats.
+    pub scale: f32,
+    /// JPEG and lossy WebP quality, 1-100.
+    pub quality: i32,
+    pub webp_lossless: bool,
+    /// Leave the background out for formats that support transparency.
+    pub transparent: bool,
+    /// Canvas units around the content for board and selection exports.
+    pub padding: f32,
+    /// Pixels per canvas unit for Copy as PNG.
+    pub copy_scale: f32,
+    /// Leave the background out of Copy as PNG.
+    pub copy_transparent: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            format: ExportFormat::Png,
+            scale: 2.0,
+            quality: 90,
+            webp_lossless: false,
+            transparent: false,
+            padding: 50.0,
+            copy_scale: 2.0,
+            copy_transparent: false,
+        }
+    }
+}
+
+impl ExportSettings {
+    /// Background to draw: the board color, or fully transparent when
+    /// requested and supported by the format.
+    pub fn background(&self, board_bg: egui::Color32) -> egui::Color32 {
+        if self.transparent && self.format.supports_transparency() {
+            egui::Color32::TRANSPARENT
+        } else {
+            board_bg
+        }
+    }
+}
+
+/// Shapes, canvas region and output resolution of one export.
+pub struct ExportTarget {
+    pub shapes: Vec<Shape>,
+    pub region: egui::Rect,
+    /// Pixels per canvas unit for raster formats.
+    pub scale: f32,
+    /// PDF pages, in canvas coordinates.
+    pub pages: Vec<egui::Rect>,
+}
+
+impl ExportTarget {
+    /// Target covering all of `shapes` plus `padding`, with one PDF page per
+    /// section among them.
+    pub fn content(shapes: Vec<Shape>, padding: f32, scale: f32) -> Result<Self, String> {
+        let region = content_bounds(&shapes)?.expand(padding);
+        let mut pages: Vec<egui::Rect> = sections_in_reading_order(&shapes)
+            .into_iter()
+            .map(|(_, r)| r)
+            .collect();
+        if pages.is_empty() {
+            pages.push(region);
+        }
+        Ok(ExportTarget {
+            shapes,
+            region,
+            scale,
+            pages,
+        })
+    }
+
+    /// Target covering exactly `region`, with only the shapes that touch it.
+    pub fn clipped(shapes: &[Shape], region: egui::Rect, scale: f32) -> Self {
+        ExportTarget {
+            shapes: shapes
+                .iter()
+                .filter(|shape| shape.get_bounds().intersects(region))
+                .cloned()
+                .collect(),
+            region,
+            scale,
+            pages: vec![region],
+        }
+    }
+
+    pub fn pixel_size(&self) -> [u32; 2] {
+        [
+            (self.region.width() * self.scale).round().max(0.0) as u32,
+            (self.region.height() * self.scale).round().max(0.0) as u32,
+        ]
+    }
+}
+
+/// Write `target` to `path` in the format chosen in `settings`. Raster
+/// formats report progress as (tiles done, total tiles).
+pub fn write_target(
+    target: &ExportTarget,
+    settings: &ExportSettings,
+    board_bg: egui::Color32,
+    path: &Path,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<(), String> {
+    let bg_color = settings.background(board_bg);
+    match settings.format {
+        ExportFormat::Svg => export_canvas_to_svg(&target.shapes, bg_color, target.region, path),
+        ExportFormat::Pdf => export_canvas_to_pdf(&target.shapes, bg_color, &target.pages, path),
+        ExportFormat::Html => export_canvas_to_html(&target.shapes, bg_color, target.region, path),
+        ExportFormat::Png | ExportFormat::Jpeg | ExportFormat::Webp => {
+            export_canvas_to_image(target, bg_color, path, settings, progress)
+        }
+    }
+}
+
+/// Bounding box of all shapes in canvas units.
+pub fn content_bounds(shapes: &[Shape]) -> Result<egui::Rect, String> {
+    if shapes.is_empty() {
+        return Err("Cannot export an empty canvas".to_string());
+    }
+
+    let mut bounds = egui::Rect::NOTHING;
+    for shape in shapes {
+        let sb = shape.get_bounds();
+        if sb.is_positive() {
+            bounds = bounds.union(sb);
+        }
+    }
+
+    if !bounds.is_positive() {
+        return Err("No exportable elements found on canvas".to_string());
+    }
+    Ok(bounds)
+}
+
+
+/// Ids and rects of all section boxes in reading order: rows from top to
+/// bottom, left to right within a row. A section starts a new row unless its
+/// top edge lies above the vertical middle of the row's first section.
+pub fn sections_in_reading_order(shapes: &[Shape]) -> Vec<(usize, egui::Rect)> {
+    let mut sections: Vec<(usize, egui::Rect)> = shapes
+        .iter()
+        .filter_map(|shape| match &shape.data {
+            ShapeData::SectionBox { rect, .. } if rect.is_positive() => Some((shape.id, *rect)),
+            _ => None,
+        })
+        .collect();
+    sections.sort_by(|a, b| a.1.min.y.total_cmp(&b.1.min.y));
+
+    let mut rows: Vec<Vec<(usize, egui::Rect)>> = Vec::new();
+    for section in sections {
+        match rows.last_mut() {
+            Some(row) if section.1.min.y < row[0].1.center().y => row.push(section),
+            _ => rows.push(vec![section]),
+        }
+    }
+    rows.into_iter()
+        .flat_map(|mut row| {
+            row.sort_by(|a, b| a.1.min.x.total_cmp(&b.1.min.x));
+            row
+        })
+        .collect()
+}
+
+/// Title of the section at `rect`: the first line of the topmost text shape
+/// placed inside it.
+pub fn section_title(shapes: &[Shape], rect: egui::Rect) -> Option<String> {
+    shapes
+        .iter()
+        .filter_map(|shape| match &shape.data {
+            ShapeData::Text { pos, text, .. } if rect.contains(*pos) => {
+                let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+                Some((pos.y, line.to_string()))
+            }
+            _ => None,
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, title)| title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(x: f32, y: f32) -> Shape {
+        let rect = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(100.0, 100.0));
+        Shape::new_section(0, rect, egui::Color32::WHITE)
+    }
+
+    #[test]
+    fn test_transparent_background_only_where_supported() {
+        let bg = egui::Color32::from_rgb(20, 20, 20);
+        let mut settings = ExportSettings {
+            transparent: true,
+            ..ExportSettings::default()
+        };
+        assert_eq!(settings.background(bg), egui::Color32::TRANSPARENT);
+        settings.format = ExportFormat::Jpeg;
+        assert_eq!(settings.background(bg), bg);
+        settings.transparent = false;
+        settings.format = ExportFormat::Webp;
+        assert_eq!(settings.background(bg), bg);
+    }
+
+    #[test]
+    fn test_section_title_uses_topmost_text_inside() {
+        let text = |y: f32, s: &str| {
+            Shape::new_text(0, egui::pos2(10.0, y), s.to_string(), egui::Color32::WHITE, 16.0)
+        };
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(100.0, 100.0));
+        let shapes = vec![
+            text(50.0, "Body"),
+            text(5.0, "\n  Moodboard A \nmore"),
+            text(-20.0, "Outside"),
+        ];
+        assert_eq!(section_title(&shapes, rect).as_deref(), Some("Moodboard A"));
+        assert_eq!(section_title(&shapes[2..], rect), None);
+    }
+
+    #[test]
+    fn test_sections_in_reading_order() {
+        let shapes = vec![
+            section(300.0, 10.0),
+            section(0.0, 200.0),
+            section(0.0, 0.0),
+            section(150.0, 30.0),
+        ];
+        let order: Vec<(f32, f32)> = sections_in_reading_order(&shapes)
+            .iter()
+            .map(|(_, r)| (r.min.x, r.min.y))
+            .collect();
+        assert_eq!(
+            order,
+            vec![(0.0, 0.0), (150.0, 30.0), (300.0, 10.0), (0.0, 200.0)]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::export::raster::{draw_shape_to_skia, to_skia_color, DrawResources};
+use crate::shapes::{Shape, ShapeData};
+use eframe::egui;
+use std::path::Path;
+
+/// Write a PDF with one page per canvas rect in `pages`. One canvas unit maps
+/// to one PDF point. Text is drawn with the embedded export font, so it stays
+/// selectable.
+pub fn export_canvas_to_pdf(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    pages: &[egui::Rect],
+    export_path: &Path,
+) -> Result<(), String> {
+    if pages.is_empty() {
+        return Err("Nothing to export".to_string());
+    }
+
+    let title = export_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let metadata = skia_safe::pdf::Metadata {
+        title,
+        creator: "Kugel".to_string(),
+        ..Default::default()
+    };
+
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut document = skia_safe::pdf::new_document(&mut bytes, Some(&metadata));
+    let mut resources = DrawResources::new();
+    for &page_rect in pages {
+        let mut page = document.begin_page((page_rect.width(), page_rect.height()), None);
+        let canvas = page.canvas();
+        canvas.clear(to_skia_color(bg_color));
+        canvas.save();
+        canvas.translate((-page_rect.min.x, -page_rect.min.y));
+        canvas.clip_rect(
+            skia_safe::Rect::new(
+                page_rect.min.x,
+                page_rect.min.y,
+                page_rect.max.x,
+                page_rect.max.y,
+            ),
+            None,
+            None,
+        );
+        for shape in shapes {
+            // The page's own frame would only outline the paper edge.
+            if matches!(&shape.data, ShapeData::SectionBox { rect, .. } if *rect == page_rect) {
+                continue;
+            }
+            if shape.get_bounds().intersects(page_rect) {
+                draw_shape_to_skia(canvas, shape, &mut resources)?;
+            }
+        }
+        canvas.restore();
+        document = page.end_page();
+    }
+    document.close();
+
+    std::fs::write(export_path, bytes).map_err(|e| format!("Failed to write export file: {}", e))
+}
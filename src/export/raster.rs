@@ -0,0 +1,524 @@
+use crate::export::tiles::{check_pixel_limits, plan_bands, Band, Tile};
+use crate::export::{content_bounds, ExportFormat, ExportSettings, ExportTarget};
+use crate::export::text::wrap_text_to_lines;
+use crate::shapes::{connector, BlendMode, Shape, ShapeData};
+use eframe::egui;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+/// Canvas units drawn around each tile so strokes and link titles reaching
+/// in from shapes just outside it are not cut off.
+const TILE_OVERDRAW: f32 = 64.0;
+
+/// Render `target` tile by tile and save it in the raster format chosen in
+/// `settings`. PNG bands are streamed into the encoder as they are rendered;
+/// JPEG and WebP are stitched first because their encoders need the whole
+/// image. `progress` is called with (tiles done, total tiles).
+pub fn export_canvas_to_image(
+    target: &ExportTarget,
+    bg_color: egui::Color32,
+    export_path: &Path,
+    settings: &ExportSettings,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<(), String> {
+    let [width, height] = target.pixel_size();
+    check_pixel_limits(settings.format, [width, height])?;
+    let row_bytes = width as usize * 4;
+
+    if settings.format == ExportFormat::Png {
+        let bands = plan_bands(width, height);
+        let total = bands.iter().map(|band| band.tiles.len()).sum();
+        let mut done = 0;
+        let mut resources = DrawResources::new();
+        let mut next_band = |band: &Band| {
+            render_band(target, bg_color, width, band, &mut resources, &mut || {
+                done += 1;
+                progress(done, total);
+            })
+        };
+        let result = write_png_bands(export_path, width, height, &bands, &mut next_band);
+        if result.is_err() {
+            let _ = std::fs::remove_file(export_path);
+        }
+        return result;
+    }
+
+    let mut pixels = render_rgba(target, bg_color, progress)?;
+    let info = skia_safe::ImageInfo::new(
+        (width as i32, height as i32),
+        skia_safe::ColorType::RGBA8888,
+        skia_safe::AlphaType::Unpremul,
+        None,
+    );
+    // Encode straight from the stitched pixels into the file, without a
+    // second copy of the image or of the encoded bytes
+    let pixmap = skia_safe::Pixmap::new(&info, &mut pixels, row_bytes)
+        .ok_or_else(|| "Failed to assemble the exported image".to_string())?;
+    let file = std::fs::File::create(export_path)
+        .map_err(|e| format!("Failed to write export file: {}", e))?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    let quality = settings.quality.clamp(1, 100) as u32;
+    let (encoded, name) = match settings.format {
+        ExportFormat::Webp => {
+            let options = skia_safe::webp_encoder::Options {
+                compression: if settings.webp_lossless {
+                    skia_safe::webp_encoder::Compression::Lossless
+                } else {
+                    skia_safe::webp_encoder::Compression::Lossy
+                },
+                quality: quality as f32,
+            };
+            (skia_safe::webp_encoder::encode(&pixmap, &mut writer, &options), "WebP")
+        }
+        _ => {
+            let options = skia_safe::jpeg_encoder::Options {
+                quality,
+                ..Default::default()
+            };
+            (skia_safe::jpeg_encoder::encode(&pixmap, &mut writer, &options), "JPEG")
+        }
+    };
+    let result = if encoded {
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to write export file: {}", e))
+    } else {
+        Err(format!("Failed to encode image to {}", name))
+    };
+    if result.is_err() {
+        drop(writer);
+        let _ = std::fs::remove_file(export_path);
+    }
+    result
+}
+
+/// Unpremultiplied RGBA pixels of the whole of `target`, rendered tile by
+/// tile. `progress` is called with (tiles done, total tiles).
+pub fn render_rgba(
+    target: &ExportTarget,
+    bg_color: egui::Color32,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<Vec<u8>, String> {
+    let [width, height] = target.pixel_size();
+    let bands = plan_bands(width, height);
+    let total = bands.iter().map(|band| band.tiles.len()).sum();
+    let mut done = 0;
+    let mut resources = DrawResources::new();
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for band in &bands {
+        let mut on_tile = || {
+            done += 1;
+            progress(done, total);
+        };
+        let band_pixels = render_band(target, bg_color, width, band, &mut resources, &mut on_tile)?;
+        pixels.extend_from_slice(&band_pixels);
+    }
+    Ok(pixels)
+}
+
+/// Render the tiles of `band` and stitch them into full-width rows.
+fn render_band(
+    target: &ExportTarget,
+    bg_color: egui::Color32,
+    width: u32,
+    band: &Band,
+    resources: &mut DrawResources,
+    on_tile: &mut dyn FnMut(),
+) -> Result<Vec<u8>, String> {
+    let row_bytes = width as usize * 4;
+    let mut pixels = vec![0u8; row_bytes * band.height as usize];
+    for tile in &band.tiles {
+        let tile_pixels = render_tile(target, bg_color, tile, resources)?;
+        let tile_row = tile.width as usize * 4;
+        for (row, src) in tile_pixels.chunks_exact(tile_row).enumerate() {
+            let dst = row * row_bytes + tile.x as usize * 4;
+            pixels[dst..dst + tile_row].copy_from_slice(src);
+        }
+        on_tile();
+    }
+    Ok(pixels)
+}
+
+fn write_png_bands(
+    export_path: &Path,
+    width: u32,
+    height: u32,
+    bands: &[Band],
+    render_band: &mut dyn FnMut(&Band) -> Result<Vec<u8>, String>,
+) -> Result<(), String> {
+    let write_err = |e: std::io::Error| format!("Failed to write export file: {}", e);
+    let png_err = |e: png::EncodingError| format!("Failed to encode image to PNG: {}", e);
+
+    let file = std::fs::File::create(export_path).map_err(write_err)?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(png_err)?;
+    {
+        let mut stream = writer.stream_writer().map_err(png_err)?;
+        for band in bands {
+            stream.write_all(&render_band(band)?).map_err(write_err)?;
+        }
+        stream.finish().map_err(png_err)?;
+    }
+    writer.finish().map_err(png_err)
+}
+
+/// Render one tile of `target` and return its unpremultiplied RGBA rows.
+fn render_tile(
+    target: &ExportTarget,
+    bg_color: egui::Color32,
+    tile: &Tile,
+    resources: &mut DrawResources,
+) -> Result<Vec<u8>, String> {
+    let (w, h) = (tile.width as i32, tile.height as i32);
+    let mut surface = skia_safe::surfaces::raster_n32_premul((w, h))
+        .ok_or_else(|| "Failed to create Skia surface".to_string())?;
+
+    let canvas = surface.canvas();
+    canvas.clear(to_skia_color(bg_color));
+    canvas.translate((-(tile.x as f32), -(tile.y as f32)));
+    canvas.scale((target.scale, target.scale));
+    canvas.translate((-target.region.min.x, -target.region.min.y));
+
+    let visible = egui::Rect::from_min_size(
+        target.region.min + egui::vec2(tile.x as f32, tile.y as f32) / target.scale,
+        egui::vec2(tile.width as f32, tile.height as f32) / target.scale,
+    )
+    .expand(TILE_OVERDRAW);
+    for shape in &target.shapes {
+        if shape.get_bounds().intersects(visible) {
+            draw_shape_to_skia(canvas, shape, resources)?;
+        }
+    }
+
+    let info = skia_safe::ImageInfo::new(
+        (w, h),
+        skia_safe::ColorType::RGBA8888,
+        skia_safe::AlphaType::Unpremul,
+        None,
+    );
+    let row_bytes = tile.width as usize * 4;
+    let mut pixels = vec![0u8; row_bytes * tile.height as usize];
+    if !surface.read_pixels(&info, &mut pixels, row_bytes, (0, 0)) {
+        return Err("Failed to read rendered pixels".to_string());
+    }
+    Ok(pixels)
+}
+
+/// Small PNG preview of the whole board whose longer side is at most `max_side` pixels.
+pub fn render_thumbnail(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    max_side: f32,
+) -> Result<Vec<u8>, String> {
+    let region = content_bounds(shapes)?.expand(20.0);
+    let scale = (max_side / region.width().max(region.height())).min(1.0);
+    let image = render_region(shapes, bg_color, region, scale)?;
+    let data = image
+        .encode(None, skia_safe::EncodedImageFormat::PNG, None)
+        .ok_or_else(|| "Failed to encode thumbnail".to_string())?;
+    Ok(data.as_bytes().to_vec())
+}
+
+/// Draw `region` of the canvas onto an offscreen Skia surface, `scale` pixels per canvas unit.
+fn render_region(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    region: egui::Rect,
+    scale: f32,
+) -> Result<skia_safe::Image, String> {
+    let export_width = (region.width() * scale).round() as i32;
+    let export_height = (region.height() * scale).round() as i32;
+
+    if export_width <= 0 || export_height <= 0 {
+        return Err("Invalid export dimensions".to_string());
+    }
+
+    let mut surface = skia_safe::surfaces::raster_n32_premul((export_width, export_height))
+        .ok_or_else(|| "Failed to create Skia surface".to_string())?;
+
+    let canvas = surface.canvas();
+    canvas.clear(to_skia_color(bg_color));
+
+    canvas.save();
+    canvas.scale((scale, scale));
+    canvas.translate((-region.min.x, -region.min.y));
+
+    let mut resources = DrawResources::new();
+    for shape in shapes {
+        draw_shape_to_skia(canvas, shape, &mut resources)?;
+    }
+
+    canvas.restore();
+
+    Ok(surface.image_snapshot())
+}
+
+pub(super) fn to_skia_color(c: egui::Color32) -> skia_safe::Color {
+    skia_safe::Color::from_argb(c.a(), c.r(), c.g(), c.b())
+}
+
+// Bundled font so export is deterministic and independent of system fonts.
+// Font::default() has no typeface, so draw_str would render nothing.
+const OPEN_SANS: &[u8] = include_bytes!("../../assets/fonts/OpenSans-Regular.ttf");
+
+/// Measures text width in the bundled export font, so vector exports wrap
+/// text exactly like the raster export does. Build one per text shape: the
+/// font is loaded once, not for every measurement.
+pub fn text_measurer(size: f32) -> impl Fn(&str) -> f32 {
+    let font = load_typeface().map(|typeface| skia_safe::Font::from_typeface(typeface, size));
+    move |text| font.as_ref().map_or(0.0, |font| font.measure_str(text, None).0)
+}
+
+fn load_typeface() -> Option<skia_safe::Typeface> {
+    skia_safe::FontMgr::new().new_from_data(OPEN_SANS, None)
+}
+
+/// Export font and decoded images shared by every tile or page of one
+/// export, so neither is loaded again for each tile an image or text spans.
+pub(super) struct DrawResources {
+    typeface: Option<skia_safe::Typeface>,
+    /// Raster images by shape id, decoded on first use; `None` when the
+    /// bytes could not be decoded.
+    images: HashMap<usize, Option<skia_safe::Image>>,
+}
+
+impl DrawResources {
+    pub(super) fn new() -> Self {
+        DrawResources {
+            typeface: load_typeface(),
+            images: HashMap::new(),
+        }
+    }
+
+    fn font(&self, size: f32) -> Option<skia_safe::Font> {
+        let typeface = self.typeface.clone()?;
+        Some(skia_safe::Font::from_typeface(typeface, size))
+    }
+
+    fn image(&mut self, id: usize, bytes: &[u8]) -> Option<&skia_safe::Image> {
+        self.images
+            .entry(id)
+            .or_insert_with(|| {
+                skia_safe::Image::from_encoded(skia_safe::Data::new_copy(bytes))?
+                    .make_raster_image(None, skia_safe::image::CachingHint::Disallow)
+            })
+            .as_ref()
+    }
+}
+
+fn to_skia_blend_mode(mode: BlendMode) -> skia_safe::BlendMode {
+    match mode {
+        BlendMode::Normal => skia_safe::BlendMode::SrcOver,
+        BlendMode::Multiply => skia_safe::BlendMode::Multiply,
+        BlendMode::Screen => skia_safe::BlendMode::Screen,
+        BlendMode::Overlay => skia_safe::BlendMode::Overlay,
+    }
+}
+
+/// Draw `shape` turned by its rotation around the center of its bounds. A
+/// translucent or blended shape is drawn into its own layer first, so its
+/// parts do not show through each other.
+pub(super) fn draw_shape_to_skia(
+    canvas: &skia_safe::Canvas,
+    shape: &Shape,
+    resources: &mut DrawResources,
+) -> Result<(), String> {
+    let restore_to = canvas.save_count();
+    if shape.opacity < 1.0 || !shape.blend.is_normal() {
+        let mut paint = skia_safe::Paint::default();
+        paint.set_alpha_f(shape.opacity.clamp(0.0, 1.0));
+        paint.set_blend_mode(to_skia_blend_mode(shape.blend));
+        canvas.save_layer(&skia_safe::canvas::SaveLayerRec::default().paint(&paint));
+    }
+    if shape.rotation != 0.0 {
+        let pivot = shape.pivot();
+        canvas.save();
+        canvas.rotate(
+            shape.rotation.to_degrees(),
+            Some(skia_safe::Point::new(pivot.x, pivot.y)),
+        );
+    }
+    let result = draw_shape_data(canvas, shape.id, &shape.data, resources);
+    canvas.restore_to_count(restore_to);
+    result
+}
+
+fn draw_shape_data(
+    canvas: &skia_safe::Canvas,
+    id: usize,
+    data: &ShapeData,
+    resources: &mut DrawResources,
+) -> Result<(), String> {
+    match data {
+        ShapeData::Pen { points, color, stroke_width } => {
+            if points.len() > 1 {
+                let mut path = skia_safe::Path::new();
+                path.move_to((points[0].x, points[0].y));
+                for p in points.iter().skip(1) {
+                    path.line_to((p.x, p.y));
+                }
+
+                let mut paint = skia_safe::Paint::default();
+                paint.set_anti_alias(true);
+                paint.set_style(skia_safe::paint::Style::Stroke);
+                paint.set_color(to_skia_color(*color));
+                paint.set_stroke_width(*stroke_width);
+                paint.set_stroke_cap(skia_safe::PaintCap::Round);
+                paint.set_stroke_join(skia_safe::PaintJoin::Round);
+
+                canvas.draw_path(&path, &paint);
+            }
+        }
+        ShapeData::Line { start, end, color, stroke_width } => {
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_style(skia_safe::paint::Style::Stroke);
+            paint.set_color(to_skia_color(*color));
+            paint.set_stroke_width(*stroke_width);
+            paint.set_stroke_cap(skia_safe::PaintCap::Round);
+            canvas.draw_line((start.x, start.y), (end.x, end.y), &paint);
+        }
+        ShapeData::Rectangle { rect, color, stroke_width, filled } => {
+            let sk_rect = skia_safe::Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y);
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(to_skia_color(*color));
+
+            if *filled {
+                paint.set_style(skia_safe::paint::Style::Fill);
+                canvas.draw_rect(sk_rect, &paint);
+            } else {
+                paint.set_style(skia_safe::paint::Style::Stroke);
+                paint.set_stroke_width(*stroke_width);
+                canvas.draw_rect(sk_rect, &paint);
+            }
+        }
+        ShapeData::Circle { center, radius, color, stroke_width, filled } => {
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(to_skia_color(*color));
+
+            if *filled {
+                paint.set_style(skia_safe::paint::Style::Fill);
+                canvas.draw_circle((center.x, center.y), *radius, &paint);
+            } else {
+                paint.set_style(skia_safe::paint::Style::Stroke);
+                paint.set_stroke_width(*stroke_width);
+                canvas.draw_circle((center.x, center.y), *radius, &paint);
+            }
+        }
+        ShapeData::Text { pos, text, color, size, max_width, link_title, .. } => {
+            if let Some(lt) = link_title {
+                let mut title_paint = skia_safe::Paint::default();
+                title_paint.set_anti_alias(true);
+                title_paint.set_color(skia_safe::Color::from_rgb(147, 197, 253));
+                if let Some(font) = resources.font(13.0) {
+                    canvas.draw_str(lt, (pos.x, pos.y - 4.0), &font, &title_paint);
+                }
+            }
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(to_skia_color(*color));
+
+            if let Some(font) = resources.font(*size) {
+                let line_height = *size * 1.25;
+                let lines = wrap_text_to_lines(text, *max_width, |s| font.measure_str(s, None).0);
+                for (i, line) in lines.iter().enumerate() {
+                    let y = pos.y + size * 0.8 + (i as f32 * line_height);
+                    canvas.draw_str(line, (pos.x, y), &font, &paint);
+                }
+            }
+        }
+        ShapeData::Image { rect, bytes, .. } => {
+            if let Some(skia_img) = resources.image(id, bytes) {
+                let sk_rect = skia_safe::Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y);
+                let paint = skia_safe::Paint::default();
+                canvas.draw_image_rect(
+                    skia_img,
+                    None,
+                    &sk_rect,
+                    &paint,
+                );
+            }
+        }
+        ShapeData::StickyNote { rect, text, bg_color, text_color, text_size, link_title, .. } => {
+            if let Some(lt) = link_title {
+                let mut title_paint = skia_safe::Paint::default();
+                title_paint.set_anti_alias(true);
+                title_paint.set_color(skia_safe::Color::from_rgb(147, 197, 253));
+                if let Some(font) = resources.font(13.0) {
+                    canvas.draw_str(lt, (rect.min.x, rect.min.y - 4.0), &font, &title_paint);
+                }
+            }
+            let sk_rect = skia_safe::Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y);
+            
+            let mut bg_paint = skia_safe::Paint::default();
+            bg_paint.set_anti_alias(true);
+            bg_paint.set_color(to_skia_color(*bg_color));
+            bg_paint.set_style(skia_safe::paint::Style::Fill);
+            
+            let rrect = skia_safe::RRect::new_rect_xy(sk_rect, 6.0, 6.0);
+            canvas.draw_rrect(rrect, &bg_paint);
+
+            let mut text_paint = skia_safe::Paint::default();
+            text_paint.set_anti_alias(true);
+            text_paint.set_color(to_skia_color(*text_color));
+            let padding = 8.0;
+            let text_width = (rect.width() - padding * 2.0).max(10.0);
+            if let Some(font) = resources.font(*text_size) {
+                let line_height = *text_size * 1.25;
+                let lines = wrap_text_to_lines(text, Some(text_width), |s| font.measure_str(s, None).0);
+                for (i, line) in lines.iter().enumerate() {
+                    let y = rect.min.y + padding + text_size * 0.8 + (i as f32 * line_height);
+                    canvas.draw_str(line, (rect.min.x + padding, y), &font, &text_paint);
+                }
+            }
+        }
+        ShapeData::SectionBox { rect, color } => {
+            let sk_rect = skia_safe::Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y);
+            let rrect = skia_safe::RRect::new_rect_xy(sk_rect, 4.0, 4.0);
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_color(to_skia_color(*color));
+            paint.set_style(skia_safe::paint::Style::Stroke);
+            paint.set_stroke_width(1.5);
+            canvas.draw_rrect(rrect, &paint);
+        }
+        ShapeData::Connector { start, end, color, stroke_width, arrows, label, .. } => {
+            let (line, heads) = connector::connector_geometry(*start, *end, *stroke_width, *arrows);
+            let mut paint = skia_safe::Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_style(skia_safe::paint::Style::Stroke);
+            paint.set_color(to_skia_color(*color));
+            paint.set_stroke_width(*stroke_width);
+            paint.set_stroke_cap(skia_safe::PaintCap::Round);
+            canvas.draw_line((line[0].x, line[0].y), (line[1].x, line[1].y), &paint);
+
+            paint.set_style(skia_safe::paint::Style::Fill);
+            for [tip, left, right] in heads {
+                let mut path = skia_safe::Path::new();
+                path.move_to((tip.x, tip.y));
+                path.line_to((left.x, left.y));
+                path.line_to((right.x, right.y));
+                path.close();
+                canvas.draw_path(&path, &paint);
+            }
+
+            if !label.is_empty() {
+                if let Some(font) = resources.font(connector::LABEL_SIZE) {
+                    let pos = connector::label_pos(*start, *end);
+                    let line_height = connector::LABEL_SIZE * 1.25;
+                    for (i, line) in label.lines().enumerate() {
+                        let y = pos.y + connector::LABEL_SIZE * 0.8 + i as f32 * line_height;
+                        canvas.draw_str(line, (pos.x, y), &font, &paint);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
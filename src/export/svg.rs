@@ -0,0 +1,457 @@
+use crate::export::raster::text_measurer;
+use crate::export::text::wrap_text_to_lines;
+use crate::shapes::{connector, Shape, ShapeData};
+use base64::Engine;
+use eframe::egui;
+use std::fmt::Write;
+use std::path::Path;
+
+const FONT_FAMILY: &str = "'Open Sans', sans-serif";
+const LINK_TITLE_COLOR: egui::Color32 = egui::Color32::from_rgb(147, 197, 253);
+
+pub fn export_canvas_to_svg(
+    shapes: &[Shape],
+    bg_color: egui::Color32,
+    region: egui::Rect,
+    export_path: &Path,
+) -> Result<(), String> {
+    let svg = render_svg(shapes, bg_color, region);
+    std::fs::write(export_path, svg).map_err(|e| format!("Failed to write export file: {}", e))
+}
+
+/// Build an SVG document of `region` with one element per shape. Text is
+/// broken into one `<text>` element per line using the same wrapping as the
+/// raster export, so line breaks survive in tools without the Open Sans font.
+pub fn render_svg(shapes: &[Shape], bg_color: egui::Color32, region: egui::Rect) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push_str(&svg_element(shapes, bg_color, region));
+    out
+}
+
+/// The `<svg>` element of `render_svg`, without the XML declaration, for
+/// embedding in other documents. Shapes with a web link are wrapped in `<a>`.
+pub fn svg_element(shapes: &[Shape], bg_color: egui::Color32, region: egui::Rect) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        num(region.width()),
+        num(region.height()),
+        num(region.min.x),
+        num(region.min.y),
+        num(region.width()),
+        num(region.height())
+    );
+    if bg_color.a() > 0 {
+        let _ = writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            num(region.min.x),
+            num(region.min.y),
+            num(region.width()),
+            num(region.height()),
+            fill(bg_color)
+        );
+    }
+    for shape in shapes {
+        let link = shape.data.link_url().filter(|url| is_web_link(url));
+        if let Some(url) = link {
+            let _ = writeln!(
+                out,
+                r#"<a href="{0}" xlink:href="{0}" target="_blank">"#,
+                escape(url)
+            );
+        }
+        let attrs = group_attrs(shape);
+        if attrs.is_empty() {
+            write_shape(&mut out, &shape.data);
+        } else {
+            let _ = writeln!(out, "<g{}>", attrs);
+            write_shape(&mut out, &shape.data);
+            out.push_str("</g>\n");
+        }
+        if link.is_some() {
+            out.push_str("</a>\n");
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Only http(s) links are exported; anything else could run script when
+/// the file is opened in a browser.
+fn is_web_link(url: &str) -> bool {
+    let lower = url.trim_start().to_ascii_lowercase();
+    lower.starts_with("https://") || lower.starts_with("http://")
+}
+
+fn write_shape(out: &mut String, data: &ShapeData) {
+    match data {
+        ShapeData::Pen {
+            points,
+            color,
+            stroke_width,
+        } => {
+            if points.len() > 1 {
+                let mut d = format!("M {} {}", num(points[0].x), num(points[0].y));
+                for p in points.iter().skip(1) {
+                    let _ = write!(d, " L {} {}", num(p.x), num(p.y));
+                }
+                let _ = writeln!(
+                    out,
+                    r#"<path d="{}" fill="none" {} stroke-linecap="round" stroke-linejoin="round"/>"#,
+                    d,
+                    stroke(*color, *stroke_width)
+                );
+            }
+        }
+        ShapeData::Line {
+            start,
+            end,
+            color,
+            stroke_width,
+        } => {
+            let _ = writeln!(
+                out,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-linecap="round"/>"#,
+                num(start.x),
+                num(start.y),
+                num(end.x),
+                num(end.y),
+                stroke(*color, *stroke_width)
+            );
+        }
+        ShapeData::Rectangle {
+            rect,
+            color,
+            stroke_width,
+            filled,
+        } => {
+            let paint = if *filled {
+                fill(*color)
+            } else {
+                format!(r#"fill="none" {}"#, stroke(*color, *stroke_width))
+            };
+            let _ = writeln!(out, r#"<rect {} {}/>"#, rect_attrs(rect), paint);
+        }
+        ShapeData::Circle {
+            center,
+            radius,
+            color,
+            stroke_width,
+            filled,
+        } => {
+            let paint = if *filled {
+                fill(*color)
+            } else {
+                format!(r#"fill="none" {}"#, stroke(*color, *stroke_width))
+            };
+            let _ = writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                num(center.x),
+                num(center.y),
+                num(*radius),
+                paint
+            );
+        }
+        ShapeData::Text {
+            pos,
+            text,
+            color,
+            size,
+            max_width,
+            link_title,
+            ..
+        } => {
+            if let Some(lt) = link_title {
+                write_text_line(out, lt, pos.x, pos.y - 4.0, 13.0, LINK_TITLE_COLOR);
+            }
+            let line_height = size * 1.25;
+            let lines = wrap_text_to_lines(text, *max_width, text_measurer(*size));
+            for (i, line) in lines.iter().enumerate() {
+                let y = pos.y + size * 0.8 + (i as f32 * line_height);
+                write_text_line(out, line, pos.x, y, *size, *color);
+            }
+        }
+        ShapeData::Image { rect, bytes, .. } => {
+            let mime = image::guess_format(bytes)
+                .map(|f| f.to_mime_type())
+                .unwrap_or("image/png");
+            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+            let _ = writeln!(
+                out,
+                r#"<image {} preserveAspectRatio="none" xlink:href="data:{};base64,{}"/>"#,
+                rect_attrs(rect),
+                mime,
+                encoded
+            );
+        }
+        ShapeData::StickyNote {
+            rect,
+            text,
+            bg_color,
+            text_color,
+            text_size,
+            link_title,
+            ..
+        } => {
+            if let Some(lt) = link_title {
+                write_text_line(
+                    out,
+                    lt,
+                    rect.min.x,
+                    rect.min.y - 4.0,
+                    13.0,
+                    LINK_TITLE_COLOR,
+                );
+            }
+            let _ = writeln!(
+                out,
+                r#"<rect {} rx="6" ry="6" {}/>"#,
+                rect_attrs(rect),
+                fill(*bg_color)
+            );
+            let padding = 8.0;
+            let text_width = (rect.width() - padding * 2.0).max(10.0);
+            let line_height = text_size * 1.25;
+            let lines = wrap_text_to_lines(text, Some(text_width), text_measurer(*text_size));
+            for (i, line) in lines.iter().enumerate() {
+                let y = rect.min.y + padding + text_size * 0.8 + (i as f32 * line_height);
+                write_text_line(out, line, rect.min.x + padding, y, *text_size, *text_color);
+            }
+        }
+        ShapeData::SectionBox { rect, color } => {
+            let _ = writeln!(
+                out,
+                r#"<rect {} rx="4" ry="4" fill="none" {}/>"#,
+                rect_attrs(rect),
+                stroke(*color, 1.5)
+            );
+        }
+        ShapeData::Connector {
+            start,
+            end,
+            color,
+            stroke_width,
+            arrows,
+            label,
+            ..
+        } => {
+            let (line, heads) = connector::connector_geometry(*start, *end, *stroke_width, *arrows);
+            let _ = writeln!(
+                out,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {} stroke-linecap="round"/>"#,
+                num(line[0].x),
+                num(line[0].y),
+                num(line[1].x),
+                num(line[1].y),
+                stroke(*color, *stroke_width)
+            );
+            for head in heads {
+                let points: Vec<String> = head
+                    .iter()
+                    .map(|p| format!("{},{}", num(p.x), num(p.y)))
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    r#"<polygon points="{}" {}/>"#,
+                    points.join(" "),
+                    fill(*color)
+                );
+            }
+            let pos = connector::label_pos(*start, *end);
+            let line_height = connector::LABEL_SIZE * 1.25;
+            for (i, line) in label.lines().enumerate() {
+                let y = pos.y + connector::LABEL_SIZE * 0.8 + (i as f32 * line_height);
+                write_text_line(out, line, pos.x, y, connector::LABEL_SIZE, *color);
+            }
+        }
+    }
+}
+
+/// Attributes of the `<g>` wrapping a rotated, translucent or blended shape,
+/// each with a leading space; empty when no wrapper is needed.
+fn group_attrs(shape: &Shape) -> String {
+    let mut attrs = String::new();
+    if shape.rotation != 0.0 {
+        let pivot = shape.pivot();
+        let _ = write!(
+            attrs,
+            r#" transform="rotate({} {} {})""#,
+            num(shape.rotation.to_degrees()),
+            num(pivot.x),
+            num(pivot.y)
+        );
+    }
+    if shape.opacity < 1.0 {
+        let _ = write!(attrs, r#" opacity="{}""#, num(shape.opacity.max(0.0)));
+    }
+    if !shape.blend.is_normal() {
+        let _ = write!(attrs, r#" style="mix-blend-mode:{}""#, shape.blend.css());
+    }
+    attrs
+}
+
+fn write_text_line(out: &mut String, text: &str, x: f32, y: f32, size: f32, color: egui::Color32) {
+    let _ = writeln!(
+        out,
+        r#"<text x="{}" y="{}" font-family="{}" font-size="{}" xml:space="preserve" {}>{}</text>"#,
+        num(x),
+        num(y),
+        FONT_FAMILY,
+        num(size),
+        fill(color),
+        escape(text)
+    );
+}
+
+fn rect_attrs(rect: &egui::Rect) -> String {
+    format!(
+        r#"x="{}" y="{}" width="{}" height="{}""#,
+        num(rect.min.x),
+        num(rect.min.y),
+        num(rect.width()),
+        num(rect.height())
+    )
+}
+
+/// `rgb()` plus a separate opacity, since SVG 1.1 consumers do not all accept `rgba()`.
+fn color_parts(color: egui::Color32) -> (String, Option<String>) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let rgb = format!("rgb({},{},{})", r, g, b);
+    let opacity = (a < 255).then(|| num(a as f32 / 255.0));
+    (rgb, opacity)
+}
+
+fn fill(color: egui::Color32) -> String {
+    match color_parts(color) {
+        (rgb, Some(opacity)) => format!(r#"fill="{}" fill-opacity="{}""#, rgb, opacity),
+        (rgb, None) => format!(r#"fill="{}""#, rgb),
+    }
+}
+
+fn stroke(color: egui::Color32, width: f32) -> String {
+    match color_parts(color) {
+        (rgb, Some(opacity)) => format!(
+            r#"stroke="{}" stroke-opacity="{}" stroke-width="{}""#,
+            rgb,
+            opacity,
+            num(width)
+        ),
+        (rgb, None) => format!(r#"stroke="{}" stroke-width="{}""#, rgb, num(width)),
+    }
+}
+
+/// Compact number formatting: at most two decimals, no trailing zeros.
+pub(super) fn num(v: f32) -> String {
+    let s = format!("{:.2}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::content_bounds;
+    use crate::shapes::BlendMode;
+
+    #[test]
+    fn test_num_formatting() {
+        assert_eq!(num(10.0), "10");
+        assert_eq!(num(1.5), "1.5");
+        assert_eq!(num(0.333), "0.33");
+        assert_eq!(num(-0.001), "0");
+    }
+
+    #[test]
+    fn test_render_svg_maps_shapes() {
+        let shapes = vec![
+            Shape::new_rect(
+                0,
+                egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(100.0, 50.0)),
+                egui::Color32::from_rgb(255, 0, 0),
+                2.0,
+                true,
+            ),
+            Shape::new_pen(
+                1,
+                vec![egui::pos2(0.0, 0.0), egui::pos2(10.0, 5.0)],
+                egui::Color32::WHITE,
+                3.0,
+            ),
+            Shape::new_image(
+                2,
+                egui::Rect::from_min_size(egui::pos2(20.0, 20.0), egui::vec2(10.0, 10.0)),
+                vec![1, 2, 3].into(),
+                [10.0, 10.0],
+                None,
+            ),
+        ];
+        let region = content_bounds(&shapes).unwrap().expand(50.0);
+        let svg = render_svg(&shapes, egui::Color32::BLACK, region);
+        assert!(svg.contains(r#"viewBox="-50 -50 200 150""#));
+        assert!(svg.contains(r#"<rect x="0" y="0" width="100" height="50" fill="rgb(255,0,0)"/>"#));
+        assert!(svg.contains(r#"<path d="M 0 0 L 10 5" fill="none""#));
+        assert!(svg.contains("data:image/png;base64,AQID"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_opacity_and_blend_mode_wrap_the_shape() {
+        let mut shape = Shape::new_rect(
+            0,
+            egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(10.0, 10.0)),
+            egui::Color32::RED,
+            1.0,
+            true,
+        );
+        assert_eq!(group_attrs(&shape), "");
+        shape.opacity = 0.5;
+        shape.blend = BlendMode::Multiply;
+        assert_eq!(
+            group_attrs(&shape),
+            r#" opacity="0.5" style="mix-blend-mode:multiply""#
+        );
+    }
+
+    #[test]
+    fn test_only_web_links_are_wrapped() {
+        let mut link = Shape::new_text(
+            0,
+            egui::pos2(0.0, 0.0),
+            "a".to_string(),
+            egui::Color32::WHITE,
+            16.0,
+        );
+        link.data
+            .set_link_url(Some("https://example.com/?a=1&b=2".to_string()));
+        let mut script = link.clone();
+        script
+            .data
+            .set_link_url(Some("javascript:alert(1)".to_string()));
+        let region = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(10.0, 10.0));
+
+        let svg = svg_element(&[link, script], egui::Color32::BLACK, region);
+        assert!(svg.contains(r#"<a href="https://example.com/?a=1&amp;b=2""#));
+        assert!(!svg.contains("javascript"));
+        assert_eq!(svg.matches("</a>").count(), 1);
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape(r#"a < b & "c""#), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,77 @@
+/// Split `text` into lines no wider than `max_width`, breaking at spaces and
+/// inside words that do not fit on a line of their own. `measure` returns the
+/// rendered width of a string in the target font.
+pub fn wrap_text_to_lines(
+    text: &str,
+    max_width: Option<f32>,
+    measure: impl Fn(&str) -> f32,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for raw_line in text.split('\n') {
+        let raw_line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        if let Some(mw) = max_width {
+            if mw > 0.0 {
+                let mut current_line = String::new();
+                let words: Vec<&str> = raw_line.split(' ').collect();
+                for word in words {
+                    if current_line.is_empty() {
+                        let w = measure(word);
+                        if w > mw {
+                            for ch in word.chars() {
+                                let mut test_str = current_line.clone();
+                                test_str.push(ch);
+                                let test_w = measure(&test_str);
+                                if test_w > mw && !current_line.is_empty() {
+                                    lines.push(current_line);
+                                    current_line = ch.to_string();
+                                } else {
+                                    current_line.push(ch);
+                                }
+                            }
+                        } else {
+                            current_line.push_str(word);
+                        }
+                    } else {
+                        let test_str = format!("{} {}", current_line, word);
+                        let test_w = measure(&test_str);
+                        if test_w > mw {
+                            lines.push(current_line);
+                            current_line = word.to_string();
+                        } else {
+                            current_line = test_str;
+                        }
+                    }
+                }
+                lines.push(current_line);
+            } else {
+                lines.push(raw_line.to_string());
+            }
+        } else {
+            lines.push(raw_line.to_string());
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every character is 10 units wide.
+    fn measure(s: &str) -> f32 {
+        s.chars().count() as f32 * 10.0
+    }
+
+    #[test]
+    fn test_wrap_at_spaces() {
+        let lines = wrap_text_to_lines("aaa bbb ccc", Some(75.0), measure);
+        assert_eq!(lines, vec!["aaa bbb", "ccc"]);
+    }
+
+    #[test]
+    fn test_wrap_breaks_long_words_and_keeps_newlines() {
+        let lines = wrap_text_to_lines("abcdefgh\r\nxy", Some(30.0), measure);
+        assert_eq!(lines, vec!["abc", "def", "gh", "xy"]);
+        assert_eq!(wrap_text_to_lines("a b", None, measure), vec!["a b"]);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::export::ExportFormat;
+
+/// Largest raster export in pixels. Bigger boards need a lower scale.
+pub const MAX_EXPORT_PIXELS: u64 = 400_000_000;
+
+/// Largest Copy as PNG image. The clipboard holds it uncompressed, so the
+/// limit is far below the file export one.
+pub const MAX_COPY_PIXELS: u64 = 50_000_000;
+
+/// Longest side of one rendered tile.
+const TILE_SIZE: u32 = 2048;
+
+/// Memory budget for one band of tiles, the unit that is streamed into the
+/// encoder.
+const BAND_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Part of the output image rendered on its own surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Row of tiles spanning the full output width.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Band {
+    pub y: u32,
+    pub height: u32,
+    pub tiles: Vec<Tile>,
+}
+
+/// Refuse exports that are too large to render or encode in `format`.
+pub fn check_pixel_limits(format: ExportFormat, [width, height]: [u32; 2]) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err("Invalid export dimensions".to_string());
+    }
+    let pixels = width as u64 * height as u64;
+    if pixels > MAX_EXPORT_PIXELS {
+        return Err(format!(
+            "{} × {} px exceeds the {} megapixel limit; lower the resolution scale",
+            width,
+            height,
+            MAX_EXPORT_PIXELS / 1_000_000
+        ));
+    }
+    let max_side = match format {
+        ExportFormat::Jpeg => Some(65_535),
+        ExportFormat::Webp => Some(16_383),
+        _ => None,
+    };
+    if let Some(max_side) = max_side {
+        if width > max_side || height > max_side {
+            return Err(format!(
+                "{} images can be at most {} px on each side",
+                format.filter_name(),
+                max_side
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse Copy as PNG images above [`MAX_COPY_PIXELS`].
+pub fn check_copy_limits([width, height]: [u32; 2]) -> Result<(), String> {
+    check_pixel_limits(ExportFormat::Png, [width, height])?;
+    if width as u64 * height as u64 > MAX_COPY_PIXELS {
+        return Err(format!(
+            "{} × {} px exceeds the {} megapixel copy limit; lower the copy scale",
+            width,
+            height,
+            MAX_COPY_PIXELS / 1_000_000
+        ));
+    }
+    Ok(())
+}
+
+/// Split a `width` × `height` image into bands of tiles, top to bottom.
+pub fn plan_bands(width: u32, height: u32) -> Vec<Band> {
+    let band_height = (BAND_BYTES / (width.max(1) as u64 * 4)).clamp(1, TILE_SIZE as u64) as u32;
+    let mut bands = Vec::new();
+    let mut y = 0;
+    while y < height {
+        let band_h = band_height.min(height - y);
+        let mut tiles = Vec::new();
+        let mut x = 0;
+        while x < width {
+            let tile_w = TILE_SIZE.min(width - x);
+            tiles.push(Tile {
+                x,
+                y,
+                width: tile_w,
+                height: band_h,
+            });
+            x += tile_w;
+        }
+        bands.push(Band {
+            y,
+            height: band_h,
+            tiles,
+        });
+        y += band_h;
+    }
+    bands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_bands_covers_image_exactly() {
+        let (width, height) = (5000, 3000);
+        let bands = plan_bands(width, height);
+        assert_eq!(bands.first().unwrap().y, 0);
+        assert_eq!(bands.iter().map(|b| b.height).sum::<u32>(), height);
+        for band in &bands {
+            assert!(band.height as u64 * width as u64 * 4 <= BAND_BYTES);
+            assert_eq!(band.tiles.iter().map(|t| t.width).sum::<u32>(), width);
+            assert!(band.tiles.iter().all(|t| t.width <= TILE_SIZE && t.y == band.y));
+        }
+    }
+
+    #[test]
+    fn test_check_pixel_limits() {
+        assert!(check_pixel_limits(ExportFormat::Png, [4000, 3000]).is_ok());
+        assert!(check_pixel_limits(ExportFormat::Png, [40_000, 40_000]).is_err());
+        assert!(check_pixel_limits(ExportFormat::Webp, [20_000, 100]).is_err());
+        assert!(check_pixel_limits(ExportFormat::Png, [20_000, 100]).is_ok());
+        assert!(check_pixel_limits(ExportFormat::Jpeg, [0, 100]).is_err());
+        assert!(check_copy_limits([4000, 3000]).is_ok());
+        assert!(check_copy_limits([10_000, 10_000]).is_err());
+    }
+}
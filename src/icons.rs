@@ -18,6 +18,7 @@ pub struct Icons {
     pub text: IconPair,
     pub note: IconPair,
     pub section: IconPair,
+    pub connector: IconPair,
     pub import: IconPair,
     pub undo: IconPair,
     pub redo: IconPair,
@@ -103,6 +104,10 @@ impl Icons {
                 "section",
                 include_bytes!("../assets/icons/square-dashed.png"),
             ),
+            connector: load(
+                "connector",
+                include_bytes!("../assets/icons/arrow-up-right.png"),
+            ),
             import: load("import", include_bytes!("../assets/icons/import.png")),
             undo: load("undo", include_bytes!("../assets/icons/undo.png")),
             redo: load("redo", include_bytes!("../assets/icons/redo.png")),
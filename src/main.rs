@@ -1,5 +1,7 @@
 mod app;
+mod assets;
 mod canvas;
+mod cli;
 mod export;
 mod icons;
 mod image_utils;
@@ -15,6 +17,16 @@ use app::App;
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(Some(command)) => std::process::exit(cli::run(command)),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("kugel: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
     // Register for the .kugel open-documents Apple Event before the event loop
     // starts, so a double-click that cold-launches the app is not dropped.
     #[cfg(target_os = "macos")]
@@ -0,0 +1,389 @@
+//! Canvas rendering of blend modes. egui composites every mesh with plain
+//! premultiplied alpha, so a blended shape is tessellated up front and drawn
+//! in a glow paint callback instead: first into a transparent offscreen
+//! layer, then composited over a copy of what is already on screen, the way
+//! exports draw it through a Skia save layer.
+
+use crate::shapes::BlendMode;
+use eframe::egui;
+use eframe::egui_glow;
+use eframe::glow::{self, HasContext as _};
+use std::sync::{Arc, Mutex};
+
+const LAYER_VERTEX: &str = r#"
+uniform vec4 u_region; // left, top, width, height in pixels
+uniform float u_pixels_per_point;
+in vec2 a_pos;
+in vec2 a_tc;
+in vec4 a_srgba;
+out vec4 v_rgba;
+out vec2 v_tc;
+
+void main() {
+    vec2 px = a_pos * u_pixels_per_point - u_region.xy;
+    gl_Position = vec4(2.0 * px.x / u_region.z - 1.0, 1.0 - 2.0 * px.y / u_region.w, 0.0, 1.0);
+    v_rgba = a_srgba / 255.0;
+    v_tc = a_tc;
+}
+"#;
+
+const LAYER_FRAGMENT: &str = r#"
+uniform sampler2D u_sampler;
+in vec4 v_rgba;
+in vec2 v_tc;
+out vec4 f_color;
+
+void main() {
+    f_color = v_rgba * texture(u_sampler, v_tc);
+}
+"#;
+
+const COMPOSITE_VERTEX: &str = r#"
+void main() {
+    vec2 corner = vec2(float(gl_VertexID & 1), float(gl_VertexID >> 1));
+    gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+// Separable blend modes as defined for CSS and Skia, on premultiplied colors.
+const COMPOSITE_FRAGMENT: &str = r#"
+uniform sampler2D u_layer;
+uniform sampler2D u_backdrop;
+uniform vec2 u_origin;
+uniform float u_opacity;
+uniform int u_mode;
+out vec4 f_color;
+
+vec3 blend(vec3 s, vec3 d) {
+    if (u_mode == 1) {
+        return s * d;
+    }
+    if (u_mode == 2) {
+        return s + d - s * d;
+    }
+    return mix(2.0 * s * d, 1.0 - 2.0 * (1.0 - s) * (1.0 - d), step(0.5, d));
+}
+
+void main() {
+    ivec2 texel = ivec2(gl_FragCoord.xy - u_origin);
+    vec4 src = texelFetch(u_layer, texel, 0) * u_opacity;
+    vec4 dst = texelFetch(u_backdrop, texel, 0);
+    vec3 s = src.a > 0.0 ? src.rgb / src.a : vec3(0.0);
+    vec3 d = dst.a > 0.0 ? dst.rgb / dst.a : vec3(0.0);
+    vec3 rgb = src.rgb * (1.0 - dst.a) + dst.rgb * (1.0 - src.a) + src.a * dst.a * blend(s, d);
+    f_color = vec4(rgb, src.a + dst.a * (1.0 - src.a));
+}
+"#;
+
+/// GL objects shared by every blended shape, created in the first callback.
+#[derive(Default)]
+enum Renderer {
+    #[default]
+    Uninit,
+    Ready(GlObjects),
+    /// The context is too old for the shaders; blended shapes draw normally.
+    Unsupported,
+}
+
+type SharedRenderer = Arc<Mutex<Renderer>>;
+
+fn shared_renderer(ctx: &egui::Context) -> SharedRenderer {
+    ctx.data_mut(|d| {
+        d.get_temp_mut_or_default::<SharedRenderer>(egui::Id::new("blend_renderer"))
+            .clone()
+    })
+}
+
+/// Whether blended shapes can be drawn on this context. True until the first
+/// attempt proves otherwise.
+pub fn is_supported(ctx: &egui::Context) -> bool {
+    let renderer = shared_renderer(ctx);
+    let supported = renderer
+        .lock()
+        .is_ok_and(|r| !matches!(*r, Renderer::Unsupported));
+    supported
+}
+
+/// Draw `shapes` as one layer blended onto what is already painted below
+/// them with `mode` at `opacity`.
+pub fn paint_blended(
+    painter: &egui::Painter,
+    shapes: Vec<egui::epaint::ClippedShape>,
+    mode: BlendMode,
+    opacity: f32,
+) {
+    let ctx = painter.ctx();
+    let meshes: Vec<egui::Mesh> = ctx
+        .tessellate(shapes, ctx.pixels_per_point())
+        .into_iter()
+        .filter_map(|clipped| match clipped.primitive {
+            egui::epaint::Primitive::Mesh(mesh) if !mesh.is_empty() => Some(mesh),
+            _ => None,
+        })
+        .collect();
+    let bounds = meshes
+        .iter()
+        .fold(egui::Rect::NOTHING, |rect, mesh| rect.union(mesh.calc_bounds()));
+    let rect = bounds.expand(1.0).intersect(painter.clip_rect());
+    if meshes.is_empty() || !rect.is_positive() {
+        return;
+    }
+
+    let renderer = shared_renderer(ctx);
+    let callback = egui_glow::CallbackFn::new(move |info, painter| {
+        let Ok(mut renderer) = renderer.lock() else {
+            return;
+        };
+        if matches!(*renderer, Renderer::Uninit) {
+            *renderer = match unsafe { GlObjects::new(painter.gl()) } {
+                Ok(objects) => Renderer::Ready(objects),
+                Err(e) => {
+                    eprintln!("Blend mode preview unavailable: {}", e);
+                    Renderer::Unsupported
+                }
+            };
+        }
+        if let Renderer::Ready(objects) = &*renderer {
+            unsafe { objects.paint(painter, &info, &meshes, mode, opacity) };
+        }
+    });
+    painter.add(egui::PaintCallback {
+        rect,
+        callback: Arc::new(callback),
+    });
+}
+
+fn mode_index(mode: BlendMode) -> i32 {
+    match mode {
+        BlendMode::Normal => 0,
+        BlendMode::Multiply => 1,
+        BlendMode::Screen => 2,
+        BlendMode::Overlay => 3,
+    }
+}
+
+struct GlObjects {
+    layer_program: glow::Program,
+    composite_program: glow::Program,
+    mesh_vao: glow::VertexArray,
+    quad_vao: glow::VertexArray,
+    vbo: glow::Buffer,
+    ebo: glow::Buffer,
+    fbo: glow::Framebuffer,
+    layer: glow::Texture,
+    backdrop: glow::Texture,
+}
+
+impl GlObjects {
+    unsafe fn new(gl: &glow::Context) -> Result<Self, String> {
+        let version = egui_glow::ShaderVersion::get(gl);
+        if !version.is_new_shader_interface() {
+            return Err(format!("{:?} shaders are not supported", version));
+        }
+        let header = format!(
+            "{}\n#ifdef GL_ES\nprecision highp float;\n#endif\n",
+            version.version_declaration()
+        );
+        let layer_program = link_program(gl, &header, LAYER_VERTEX, LAYER_FRAGMENT, |program| {
+            gl.bind_attrib_location(program, 0, "a_pos");
+            gl.bind_attrib_location(program, 1, "a_tc");
+            gl.bind_attrib_location(program, 2, "a_srgba");
+        })?;
+        let composite_program =
+            link_program(gl, &header, COMPOSITE_VERTEX, COMPOSITE_FRAGMENT, |_| {})?;
+
+        // Same vertex layout as egui_glow: pos and uv as f32, color as 4 bytes
+        let mesh_vao = gl.create_vertex_array()?;
+        let vbo = gl.create_buffer()?;
+        let ebo = gl.create_buffer()?;
+        gl.bind_vertex_array(Some(mesh_vao));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+        gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, VERTEX_BYTES, 0);
+        gl.vertex_attrib_pointer_f32(1, 2, glow::FLOAT, false, VERTEX_BYTES, 8);
+        gl.vertex_attrib_pointer_f32(2, 4, glow::UNSIGNED_BYTE, false, VERTEX_BYTES, 16);
+        for location in 0..3 {
+            gl.enable_vertex_attrib_array(location);
+        }
+        gl.bind_vertex_array(None);
+        let quad_vao = gl.create_vertex_array()?;
+
+        let fbo = gl.create_framebuffer()?;
+        let layer = create_region_texture(gl)?;
+        let backdrop = create_region_texture(gl)?;
+        Ok(GlObjects {
+            layer_program,
+            composite_program,
+            mesh_vao,
+            quad_vao,
+            vbo,
+            ebo,
+            fbo,
+            layer,
+            backdrop,
+        })
+    }
+
+    /// Leaves the callback framebuffer bound; egui_glow restores its own
+    /// program, blend state and vertex array after the callback returns.
+    unsafe fn paint(
+        &self,
+        painter: &egui_glow::Painter,
+        info: &egui::PaintCallbackInfo,
+        meshes: &[egui::Mesh],
+        mode: BlendMode,
+        opacity: f32,
+    ) {
+        let gl = painter.gl();
+        let region = info.viewport_in_pixels();
+        let (width, height) = (region.width_px, region.height_px);
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        for texture in [self.layer, self.backdrop] {
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                width,
+                height,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(None),
+            );
+        }
+
+        // The shape on its own, with normal blending inside the layer
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+        gl.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(self.layer),
+            0,
+        );
+        gl.viewport(0, 0, width, height);
+        gl.disable(glow::SCISSOR_TEST);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+        gl.use_program(Some(self.layer_program));
+        let uniform = |name: &str| gl.get_uniform_location(self.layer_program, name);
+        gl.uniform_4_f32(
+            uniform("u_region").as_ref(),
+            region.left_px as f32,
+            region.top_px as f32,
+            width as f32,
+            height as f32,
+        );
+        gl.uniform_1_f32(uniform("u_pixels_per_point").as_ref(), info.pixels_per_point);
+        gl.uniform_1_i32(uniform("u_sampler").as_ref(), 0);
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_vertex_array(Some(self.mesh_vao));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+        for mesh in meshes {
+            let Some(texture) = painter.texture(mesh.texture_id) else {
+                continue;
+            };
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &vertex_bytes(mesh), glow::STREAM_DRAW);
+            let indices: Vec<u8> = mesh.indices.iter().flat_map(|i| i.to_ne_bytes()).collect();
+            gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, &indices, glow::STREAM_DRAW);
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.draw_elements(glow::TRIANGLES, mesh.indices.len() as i32, glow::UNSIGNED_INT, 0);
+        }
+
+        // What is already drawn below the shape
+        gl.bind_framebuffer(glow::FRAMEBUFFER, painter.intermediate_fbo());
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.backdrop));
+        gl.copy_tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            0,
+            region.left_px,
+            region.from_bottom_px,
+            width,
+            height,
+        );
+
+        // Replace the region with the blended result
+        gl.viewport(region.left_px, region.from_bottom_px, width, height);
+        gl.enable(glow::SCISSOR_TEST);
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(self.composite_program));
+        let uniform = |name: &str| gl.get_uniform_location(self.composite_program, name);
+        gl.uniform_1_i32(uniform("u_layer").as_ref(), 0);
+        gl.uniform_1_i32(uniform("u_backdrop").as_ref(), 1);
+        gl.uniform_2_f32(
+            uniform("u_origin").as_ref(),
+            region.left_px as f32,
+            region.from_bottom_px as f32,
+        );
+        gl.uniform_1_f32(uniform("u_opacity").as_ref(), opacity);
+        gl.uniform_1_i32(uniform("u_mode").as_ref(), mode_index(mode));
+        gl.active_texture(glow::TEXTURE1);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.backdrop));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.layer));
+        gl.bind_vertex_array(Some(self.quad_vao));
+        gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+        gl.enable(glow::BLEND);
+    }
+}
+
+/// Size in bytes of one egui vertex: position, uv and color.
+const VERTEX_BYTES: i32 = 20;
+
+fn vertex_bytes(mesh: &egui::Mesh) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(mesh.vertices.len() * VERTEX_BYTES as usize);
+    for vertex in &mesh.vertices {
+        for value in [vertex.pos.x, vertex.pos.y, vertex.uv.x, vertex.uv.y] {
+            bytes.extend_from_slice(&value.to_ne_bytes());
+        }
+        bytes.extend_from_slice(&vertex.color.to_array());
+    }
+    bytes
+}
+
+/// Texture read with `texelFetch`, so it needs no mipmaps.
+unsafe fn create_region_texture(gl: &glow::Context) -> Result<glow::Texture, String> {
+    let texture = gl.create_texture()?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::NEAREST as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::NEAREST as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+    Ok(texture)
+}
+
+unsafe fn link_program(
+    gl: &glow::Context,
+    header: &str,
+    vertex: &str,
+    fragment: &str,
+    before_link: impl FnOnce(glow::Program),
+) -> Result<glow::Program, String> {
+    let program = gl.create_program()?;
+    let mut shaders = Vec::new();
+    for (kind, source) in [(glow::VERTEX_SHADER, vertex), (glow::FRAGMENT_SHADER, fragment)] {
+        let shader = gl.create_shader(kind)?;
+        gl.shader_source(shader, &format!("{}{}", header, source));
+        gl.compile_shader(shader);
+        if !gl.get_shader_compile_status(shader) {
+            return Err(gl.get_shader_info_log(shader));
+        }
+        gl.attach_shader(program, shader);
+        shaders.push(shader);
+    }
+    before_link(program);
+    gl.link_program(program);
+    for shader in shaders {
+        gl.detach_shader(program, shader);
+        gl.delete_shader(shader);
+    }
+    if !gl.get_program_link_status(program) {
+        return Err(gl.get_program_info_log(program));
+    }
+    Ok(program)
+}
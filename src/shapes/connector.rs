@@ -0,0 +1,242 @@
+use crate::shapes::{Shape, ShapeData};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Canvas units left between an attached connector end and the outline.
+const OUTLINE_GAP: f32 = 4.0;
+
+/// Font size of connector labels in canvas units.
+pub const LABEL_SIZE: f32 = 14.0;
+
+/// Which ends of a connector carry an arrowhead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArrowHeads {
+    None,
+    #[default]
+    End,
+    Both,
+}
+
+impl ArrowHeads {
+    pub const ALL: [ArrowHeads; 3] = [ArrowHeads::None, ArrowHeads::End, ArrowHeads::Both];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ArrowHeads::None => "No arrowheads",
+            ArrowHeads::End => "Arrow at end",
+            ArrowHeads::Both => "Arrows at both ends",
+        }
+    }
+
+    pub fn at_start(self) -> bool {
+        self == ArrowHeads::Both
+    }
+
+    pub fn at_end(self) -> bool {
+        self != ArrowHeads::None
+    }
+}
+
+/// Geometry of a connector: the stroked line, shortened so it stops at the
+/// base of each arrowhead, and the arrowhead triangles (tip first).
+pub fn connector_geometry(
+    start: egui::Pos2,
+    end: egui::Pos2,
+    stroke_width: f32,
+    arrows: ArrowHeads,
+) -> ([egui::Pos2; 2], Vec<[egui::Pos2; 3]>) {
+    let mut line = [start, end];
+    let mut heads = Vec::new();
+    if arrows.at_end() {
+        let head = arrow_head(end, start, stroke_width);
+        line[1] = head[1].lerp(head[2], 0.5);
+        heads.push(head);
+    }
+    if arrows.at_start() {
+        let head = arrow_head(start, end, stroke_width);
+        line[0] = head[1].lerp(head[2], 0.5);
+        heads.push(head);
+    }
+    (line, heads)
+}
+
+fn arrow_head(tip: egui::Pos2, from: egui::Pos2, stroke_width: f32) -> [egui::Pos2; 3] {
+    let size = (stroke_width * 3.0).max(10.0).min(tip.distance(from) * 0.5);
+    let dir = (tip - from).normalized();
+    let back = tip - dir * size;
+    let side = dir.rot90() * size * 0.5;
+    [tip, back + side, back - side]
+}
+
+/// Top-left corner of a connector's label: just above its midpoint.
+pub fn label_pos(start: egui::Pos2, end: egui::Pos2) -> egui::Pos2 {
+    start.lerp(end, 0.5) + egui::vec2(6.0, -(LABEL_SIZE + 6.0))
+}
+
+/// Point just outside the outline of `shape` on the way to `toward`.
+pub fn outline_point(shape: &Shape, toward: egui::Pos2) -> egui::Pos2 {
+    let center = shape.pivot();
+    let dir = toward - center;
+    if dir.length() < 1e-3 {
+        return center;
+    }
+    let distance = match &shape.data {
+        ShapeData::Circle { radius, .. } => *radius,
+        _ => {
+            let local = egui::emath::Rot2::from_angle(-shape.rotation) * dir;
+            let half = shape.data.get_bounds().size() / 2.0;
+            let reach = |half: f32, along: f32| {
+                if along.abs() > 1e-6 {
+                    half / along.abs()
+                } else {
+                    f32::INFINITY
+                }
+            };
+            reach(half.x, local.x).min(reach(half.y, local.y)) * local.length()
+        }
+    };
+    center + dir.normalized() * (distance + OUTLINE_GAP)
+}
+
+/// Move the attached ends of every connector onto the outline of their
+/// shape, facing the other end. Ends whose shape no longer exists are let
+/// go where they are.
+pub fn route_connectors(shapes: &mut [Shape]) {
+    let index_of: HashMap<usize, usize> = shapes
+        .iter()
+        .enumerate()
+        .filter(|(_, shape)| !matches!(shape.data, ShapeData::Connector { .. }))
+        .map(|(idx, shape)| (shape.id, idx))
+        .collect();
+
+    let mut routes = Vec::new();
+    for (idx, shape) in shapes.iter().enumerate() {
+        let ShapeData::Connector {
+            start,
+            end,
+            start_id,
+            end_id,
+            ..
+        } = &shape.data
+        else {
+            continue;
+        };
+        let attached = |id: &Option<usize>| {
+            id.and_then(|id| index_of.get(&id))
+                .map(|&target| &shapes[target])
+        };
+        let (from, to) = (attached(start_id), attached(end_id));
+        let new_start = from.map_or(*start, |s| outline_point(s, to.map_or(*end, Shape::pivot)));
+        let new_end = to.map_or(*end, |s| outline_point(s, from.map_or(*start, Shape::pivot)));
+        routes.push((idx, new_start, new_end, from.is_some(), to.is_some()));
+    }
+
+    for (idx, new_start, new_end, has_start, has_end) in routes {
+        if let ShapeData::Connector {
+            start,
+            end,
+            start_id,
+            end_id,
+            ..
+        } = &mut shapes[idx].data
+        {
+            *start = new_start;
+            *end = new_end;
+            if !has_start {
+                *start_id = None;
+            }
+            if !has_end {
+                *end_id = None;
+            }
+        }
+    }
+}
+
+/// Point connectors at the new ids of copied shapes (`ids` maps old ids to
+/// new ones). Ends attached to a shape that was not copied are detached.
+pub fn remap_connector_ids(shapes: &mut [Shape], ids: &HashMap<usize, usize>) {
+    for shape in shapes {
+        if let ShapeData::Connector {
+            start_id, end_id, ..
+        } = &mut shape.data
+        {
+            *start_id = start_id.and_then(|id| ids.get(&id).copied());
+            *end_id = end_id.and_then(|id| ids.get(&id).copied());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(id: usize, min: egui::Pos2) -> Shape {
+        Shape::new_rect(
+            id,
+            egui::Rect::from_min_size(min, egui::vec2(100.0, 50.0)),
+            egui::Color32::WHITE,
+            1.0,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_route_connectors_follows_moved_shapes() {
+        let mut connector = Shape::new_connector(
+            3,
+            egui::pos2(0.0, 0.0),
+            egui::pos2(1.0, 1.0),
+            egui::Color32::WHITE,
+            2.0,
+        );
+        if let ShapeData::Connector {
+            start_id, end_id, ..
+        } = &mut connector.data
+        {
+            *start_id = Some(1);
+            *end_id = Some(2);
+        }
+        let mut shapes = vec![
+            rect(1, egui::pos2(0.0, 0.0)),
+            rect(2, egui::pos2(300.0, 0.0)),
+            connector,
+        ];
+
+        route_connectors(&mut shapes);
+        let ShapeData::Connector { start, end, .. } = shapes[2].data else {
+            unreachable!()
+        };
+        assert_eq!(start, egui::pos2(104.0, 25.0));
+        assert_eq!(end, egui::pos2(296.0, 25.0));
+
+        shapes[1].data.translate(egui::vec2(0.0, 200.0));
+        route_connectors(&mut shapes);
+        let ShapeData::Connector { start, end, .. } = shapes[2].data else {
+            unreachable!()
+        };
+        // Leaves through the bottom edge and arrives through the top edge
+        assert!(start.y > 50.0 && start.x < 100.0);
+        assert!(end.y < 200.0 && end.x > 300.0);
+
+        shapes.remove(1);
+        route_connectors(&mut shapes);
+        let ShapeData::Connector { start_id, end_id, .. } = shapes[1].data else {
+            unreachable!()
+        };
+        assert_eq!((start_id, end_id), (Some(1), None));
+    }
+
+    #[test]
+    fn test_connector_geometry_stops_line_at_arrowheads() {
+        let (line, heads) = connector_geometry(
+            egui::pos2(0.0, 0.0),
+            egui::pos2(100.0, 0.0),
+            2.0,
+            ArrowHeads::Both,
+        );
+        assert_eq!(heads.len(), 2);
+        assert_eq!(heads[0][0], egui::pos2(100.0, 0.0));
+        assert_eq!(line, [egui::pos2(10.0, 0.0), egui::pos2(90.0, 0.0)]);
+    }
+}
@@ -2,13 +2,18 @@ use base64::Engine;
 use serde::de::{Deserializer, Error, SeqAccess, Visitor};
 use serde::Serializer;
 use std::fmt;
+use std::sync::Arc;
+
+pub fn is_empty(bytes: &[u8]) -> bool {
+    bytes.is_empty()
+}
 
 pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
     let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
     s.serialize_str(&encoded)
 }
 
-pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Arc<[u8]>, D::Error> {
     struct BytesVisitor;
 
     impl<'de> Visitor<'de> for BytesVisitor {
@@ -33,5 +38,5 @@ pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error>
         }
     }
 
-    d.deserialize_any(BytesVisitor)
+    d.deserialize_any(BytesVisitor).map(Arc::from)
 }
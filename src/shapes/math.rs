@@ -13,6 +13,11 @@ pub fn dist_to_segment(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
     p.distance(projection)
 }
 
+/// Turns `p` around `pivot` by `angle` radians, clockwise on screen.
+pub fn rotate_point(p: egui::Pos2, pivot: egui::Pos2, angle: f32) -> egui::Pos2 {
+    pivot + egui::emath::Rot2::from_angle(angle) * (p - pivot)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
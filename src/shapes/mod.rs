@@ -1,7 +1,11 @@
+pub mod blend;
+mod blend_gl;
+pub mod connector;
 mod image_bytes;
 pub mod math;
 pub mod shape_data;
 pub mod tool;
 
+pub use blend::BlendMode;
 pub use shape_data::{Shape, ShapeData};
 pub use tool::Tool;
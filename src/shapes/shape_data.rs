@@ -1,12 +1,46 @@
+use crate::shapes::blend::BlendMode;
+use crate::shapes::blend_gl;
+use crate::shapes::connector::{self, ArrowHeads};
 use crate::shapes::image_bytes;
-use crate::shapes::math::dist_to_segment;
+use crate::shapes::math::{dist_to_segment, rotate_point};
 use eframe::egui;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Shape {
     pub id: usize,
     pub data: ShapeData,
+    /// Clockwise rotation in radians around the center of `data`'s bounds.
+    #[serde(default, skip_serializing_if = "is_unrotated")]
+    pub rotation: f32,
+    /// Group this shape belongs to. Grouped shapes are selected as one unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+    /// Locked shapes cannot be selected, dragged or nudged until unlocked.
+    #[serde(default, skip_serializing_if = "is_unlocked")]
+    pub locked: bool,
+    /// Opacity from 0 to 1, applied on top of the shape's own colors.
+    #[serde(default = "full_opacity", skip_serializing_if = "is_opaque")]
+    pub opacity: f32,
+    #[serde(default, skip_serializing_if = "BlendMode::is_normal")]
+    pub blend: BlendMode,
+}
+
+fn is_unrotated(rotation: &f32) -> bool {
+    *rotation == 0.0
+}
+
+fn is_unlocked(locked: &bool) -> bool {
+    !*locked
+}
+
+fn full_opacity() -> f32 {
+    1.0
+}
+
+fn is_opaque(opacity: &f32) -> bool {
+    *opacity >= 1.0
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -53,8 +87,16 @@ pub enum ShapeData {
     },
     Image {
         rect: egui::Rect,
-        #[serde(with = "image_bytes")]
-        bytes: Vec<u8>,
+        /// Shared with every other image of identical content via `AssetStore`.
+        #[serde(
+            with = "image_bytes",
+            default = "empty_bytes",
+            skip_serializing_if = "image_bytes::is_empty"
+        )]
+        bytes: Arc<[u8]>,
+        /// Content hash of `bytes`, the key of the image in the board's asset store.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        asset: Option<String>,
         original_size: [f32; 2],
         #[serde(skip)]
         texture: Option<egui::TextureHandle>,
@@ -78,61 +120,94 @@ pub enum ShapeData {
         rect: egui::Rect,
         color: egui::Color32,
     },
+    /// Arrow between two points. Ends attached to a shape by id are kept on
+    /// that shape's outline by `connector::route_connectors`.
+    Connector {
+        start: egui::Pos2,
+        end: egui::Pos2,
+        #[serde(default)]
+        start_id: Option<usize>,
+        #[serde(default)]
+        end_id: Option<usize>,
+        color: egui::Color32,
+        stroke_width: f32,
+        #[serde(default)]
+        arrows: ArrowHeads,
+        #[serde(default)]
+        label: String,
+    },
+}
+
+fn empty_bytes() -> Arc<[u8]> {
+    Arc::from(Vec::new())
 }
 
 impl Shape {
-    pub fn new_pen(id: usize, points: Vec<egui::Pos2>, color: egui::Color32, stroke_width: f32) -> Self {
+    /// Unrotated, ungrouped, unlocked, fully opaque shape with normal blending.
+    fn with_data(id: usize, data: ShapeData) -> Self {
         Self {
             id,
-            data: ShapeData::Pen {
+            data,
+            rotation: 0.0,
+            group: None,
+            locked: false,
+            opacity: 1.0,
+            blend: BlendMode::Normal,
+        }
+    }
+
+    pub fn new_pen(id: usize, points: Vec<egui::Pos2>, color: egui::Color32, stroke_width: f32) -> Self {
+        Self::with_data(
+            id,
+            ShapeData::Pen {
                 points,
                 color,
                 stroke_width,
             },
-        }
+        )
     }
 
     pub fn new_line(id: usize, start: egui::Pos2, end: egui::Pos2, color: egui::Color32, stroke_width: f32) -> Self {
-        Self {
+        Self::with_data(
             id,
-            data: ShapeData::Line {
+            ShapeData::Line {
                 start,
                 end,
                 color,
                 stroke_width,
             },
-        }
+        )
     }
 
     pub fn new_rect(id: usize, rect: egui::Rect, color: egui::Color32, stroke_width: f32, filled: bool) -> Self {
-        Self {
+        Self::with_data(
             id,
-            data: ShapeData::Rectangle {
+            ShapeData::Rectangle {
                 rect,
                 color,
                 stroke_width,
                 filled,
             },
-        }
+        )
     }
 
     pub fn new_circle(id: usize, center: egui::Pos2, radius: f32, color: egui::Color32, stroke_width: f32, filled: bool) -> Self {
-        Self {
+        Self::with_data(
             id,
-            data: ShapeData::Circle {
+            ShapeData::Circle {
                 center,
                 radius,
                 color,
                 stroke_width,
                 filled,
             },
-        }
+        )
     }
 
     pub fn new_text(id: usize, pos: egui::Pos2, text: String, color: egui::Color32, size: f32) -> Self {
-        Self {
+        Self::with_data(
             id,
-            data: ShapeData::Text {
+            ShapeData::Text {
                 pos,
                 text,
                 color,
@@ -143,25 +218,26 @@ impl Shape {
                 cached_size: None,
                 cache_key: None,
             },
-        }
+        )
     }
 
-    pub fn new_image(id: usize, rect: egui::Rect, bytes: Vec<u8>, original_size: [f32; 2], texture: Option<egui::TextureHandle>) -> Self {
-        Self {
+    pub fn new_image(id: usize, rect: egui::Rect, bytes: Arc<[u8]>, original_size: [f32; 2], texture: Option<egui::TextureHandle>) -> Self {
+        Self::with_data(
             id,
-            data: ShapeData::Image {
+            ShapeData::Image {
                 rect,
                 bytes,
+                asset: None,
                 original_size,
                 texture,
             },
-        }
+        )
     }
 
     pub fn new_sticky_note(id: usize, rect: egui::Rect, text: String, bg_color: egui::Color32, text_color: egui::Color32, text_size: f32) -> Self {
-        Self {
+        Self::with_data(
             id,
-            data: ShapeData::StickyNote {
+            ShapeData::StickyNote {
                 rect,
                 text,
                 bg_color,
@@ -172,15 +248,143 @@ impl Shape {
                 cached_height: None,
                 cache_key: None,
             },
-        }
+        )
     }
 
     pub fn new_section(id: usize, rect: egui::Rect, color: egui::Color32) -> Self {
-        Self {
+        Self::with_data(id, ShapeData::SectionBox { rect, color })
+    }
+
+    pub fn new_connector(id: usize, start: egui::Pos2, end: egui::Pos2, color: egui::Color32, stroke_width: f32) -> Self {
+        Self::with_data(
             id,
-            data: ShapeData::SectionBox { rect, color },
+            ShapeData::Connector {
+                start,
+                end,
+                start_id: None,
+                end_id: None,
+                color,
+                stroke_width,
+                arrows: ArrowHeads::End,
+                label: String::new(),
+            },
+        )
+    }
+
+    /// Point the shape rotates around: the center of its unrotated bounds.
+    pub fn pivot(&self) -> egui::Pos2 {
+        self.data.get_bounds().center()
+    }
+
+    /// Corners of the unrotated bounds turned by `rotation`, in resize handle
+    /// order: top-left, top-right, bottom-left, bottom-right.
+    pub fn corners(&self) -> [egui::Pos2; 4] {
+        let bounds = self.data.get_bounds();
+        [
+            bounds.left_top(),
+            bounds.right_top(),
+            bounds.left_bottom(),
+            bounds.right_bottom(),
+        ]
+        .map(|p| rotate_point(p, bounds.center(), self.rotation))
+    }
+
+    /// Axis-aligned bounds of the shape as drawn, rotation included.
+    pub fn get_bounds(&self) -> egui::Rect {
+        if self.rotation == 0.0 {
+            return self.data.get_bounds();
+        }
+        let pivot = self.pivot();
+        match &self.data {
+            ShapeData::Pen { points, .. } => {
+                let rotated: Vec<egui::Pos2> = points
+                    .iter()
+                    .map(|&p| rotate_point(p, pivot, self.rotation))
+                    .collect();
+                egui::Rect::from_points(&rotated)
+            }
+            ShapeData::Line { start, end, .. } => egui::Rect::from_two_pos(
+                rotate_point(*start, pivot, self.rotation),
+                rotate_point(*end, pivot, self.rotation),
+            ),
+            ShapeData::Circle { .. } => self.data.get_bounds(),
+            _ => egui::Rect::from_points(&self.corners()),
         }
     }
+
+    pub fn contains_point(&self, point: egui::Pos2, tolerance: f32) -> bool {
+        let local = rotate_point(point, self.pivot(), -self.rotation);
+        self.data.contains_point(local, tolerance)
+    }
+
+    /// Resize by dragging handle `handle_index` to `mouse_pos`. Rotated shapes
+    /// are resized in their own frame and keep the opposite corner in place.
+    pub fn resize(&mut self, handle_index: usize, delta: egui::Vec2, mouse_pos: egui::Pos2) {
+        if self.rotation == 0.0 || matches!(self.data, ShapeData::Circle { .. }) {
+            self.data.resize(handle_index, delta, mouse_pos);
+            return;
+        }
+        let opposite = 3 - handle_index.min(3);
+        let anchor = self.corners()[opposite];
+        let local_mouse = rotate_point(mouse_pos, self.pivot(), -self.rotation);
+        let local_delta = egui::emath::Rot2::from_angle(-self.rotation) * delta;
+        self.data.resize(handle_index, local_delta, local_mouse);
+        let moved = self.corners()[opposite];
+        self.data.translate(anchor - moved);
+    }
+
+    /// Turn the shape by `angle` radians around `pivot`.
+    pub fn rotate_about(&mut self, pivot: egui::Pos2, angle: f32) {
+        // Connectors are routed from their end points and never carry a rotation.
+        if let ShapeData::Connector { start, end, .. } = &mut self.data {
+            *start = rotate_point(*start, pivot, angle);
+            *end = rotate_point(*end, pivot, angle);
+            return;
+        }
+        let center = self.pivot();
+        self.data.translate(rotate_point(center, pivot, angle) - center);
+        let turned = self.rotation + angle;
+        self.rotation = (turned + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+    }
+
+    pub fn render(&self, painter: &egui::Painter, zoom: f32, pan_offset: egui::Vec2, is_editing: bool) {
+        if self.opacity >= 1.0 && self.blend.is_normal() {
+            self.data.render(painter, zoom, pan_offset, is_editing, self.rotation);
+            return;
+        }
+        // Restyle or take back everything this shape added to the layer
+        let layer = painter.layer_id();
+        let start = painter.ctx().graphics_mut(|g| g.entry(layer).next_idx());
+        self.data.render(painter, zoom, pan_offset, is_editing, self.rotation);
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        if !self.blend.is_normal() && blend_gl::is_supported(painter.ctx()) {
+            let mut shapes = Vec::new();
+            painter.ctx().graphics_mut(|g| {
+                let list = g.entry(layer);
+                for idx in start.0..list.next_idx().0 {
+                    list.mutate_shape(egui::layers::ShapeIdx(idx), |clipped| {
+                        shapes.push(egui::epaint::ClippedShape {
+                            clip_rect: clipped.clip_rect,
+                            shape: std::mem::replace(&mut clipped.shape, egui::Shape::Noop),
+                        });
+                    });
+                }
+            });
+            blend_gl::paint_blended(painter, shapes, self.blend, opacity);
+            return;
+        }
+        painter.ctx().graphics_mut(|g| {
+            let list = g.entry(layer);
+            for idx in start.0..list.next_idx().0 {
+                list.mutate_shape(egui::layers::ShapeIdx(idx), |clipped| {
+                    egui::epaint::shape_transform::adjust_colors(&mut clipped.shape, move |color| {
+                        *color = color.gamma_multiply(opacity);
+                    });
+                });
+            }
+        });
+    }
 }
 
 impl ShapeData {
@@ -195,6 +399,7 @@ impl ShapeData {
             ShapeData::Image { .. } => "🖼 Image",
             ShapeData::StickyNote { .. } => "📝 Note",
             ShapeData::SectionBox { .. } => "⬚ Section",
+            ShapeData::Connector { .. } => "➝ Connector",
         }
     }
 
@@ -248,7 +453,9 @@ impl ShapeData {
                     rect
                 }
             }
-            ShapeData::Line { start, end, .. } => egui::Rect::from_two_pos(*start, *end),
+            ShapeData::Line { start, end, .. } | ShapeData::Connector { start, end, .. } => {
+                egui::Rect::from_two_pos(*start, *end)
+            }
             ShapeData::Rectangle { rect, .. } => *rect,
             ShapeData::Circle { center, radius, .. } => {
                 egui::Rect::from_center_size(*center, egui::Vec2::splat(radius * 2.0))
@@ -282,7 +489,8 @@ impl ShapeData {
             | ShapeData::Rectangle { color, .. }
             | ShapeData::Circle { color, .. }
             | ShapeData::Text { color, .. }
-            | ShapeData::SectionBox { color, .. } => *color = c,
+            | ShapeData::SectionBox { color, .. }
+            | ShapeData::Connector { color, .. } => *color = c,
             ShapeData::StickyNote { text_color, .. } => *text_color = c,
             ShapeData::Image { .. } => {}
         }
@@ -295,7 +503,7 @@ impl ShapeData {
                     *p += delta;
                 }
             }
-            ShapeData::Line { start, end, .. } => {
+            ShapeData::Line { start, end, .. } | ShapeData::Connector { start, end, .. } => {
                 *start += delta;
                 *end += delta;
             }
@@ -328,7 +536,7 @@ impl ShapeData {
                     *p = sp(*p);
                 }
             }
-            ShapeData::Line { start, end, .. } => {
+            ShapeData::Line { start, end, .. } | ShapeData::Connector { start, end, .. } => {
                 *start = sp(*start);
                 *end = sp(*end);
             }
@@ -418,7 +626,7 @@ impl ShapeData {
                     }
                 }
             }
-            ShapeData::Line { start, end, .. } => {
+            ShapeData::Line { start, end, .. } | ShapeData::Connector { start, end, .. } => {
                 let w = bounds.width();
                 let h = bounds.height();
                 if w > 0.0 && h > 0.0 {
@@ -570,7 +778,8 @@ impl ShapeData {
                 }
                 false
             }
-            ShapeData::Line { start, end, stroke_width, .. } => {
+            ShapeData::Line { start, end, stroke_width, .. }
+            | ShapeData::Connector { start, end, stroke_width, .. } => {
                 let check_dist = (tolerance + stroke_width / 2.0).max(8.0);
                 dist_to_segment(point, *start, *end) <= check_dist
             }
@@ -597,34 +806,29 @@ impl ShapeData {
         }
     }
 
-    pub fn load_textures(&mut self, ctx: &egui::Context, id: usize) {
-        if let ShapeData::Image { bytes, texture, .. } = self {
-            if texture.is_none() {
-                if let Ok(img) = image::load_from_memory(bytes) {
-                    let rgba = img.to_rgba8();
-                    let color_img = egui::ColorImage::from_rgba_unmultiplied(
-                        [rgba.width() as usize, rgba.height() as usize],
-                        &rgba.into_raw(),
-                    );
-                    *texture = Some(ctx.load_texture(
-                        format!("image_texture_{}", id),
-                        color_img,
-                        egui::TextureOptions {
-                            magnification: egui::TextureFilter::Linear,
-                            minification: egui::TextureFilter::Linear,
-                            mipmap_mode: Some(egui::TextureFilter::Linear),
-                            wrap_mode: egui::TextureWrapMode::ClampToEdge,
-                        },
-                    ));
-                }
-            }
-        }
-    }
-
-    pub fn render(&self, painter: &egui::Painter, zoom: f32, pan_offset: egui::Vec2, is_editing: bool) {
-        let transform = |pos: egui::Pos2| -> egui::Pos2 {
+    /// Draw the shape turned by `rotation` radians around the center of its bounds.
+    pub fn render(
+        &self,
+        painter: &egui::Painter,
+        zoom: f32,
+        pan_offset: egui::Vec2,
+        is_editing: bool,
+        rotation: f32,
+    ) {
+        let to_screen = |pos: egui::Pos2| -> egui::Pos2 {
             egui::pos2(pos.x * zoom + pan_offset.x, pos.y * zoom + pan_offset.y)
         };
+        let pivot = self.get_bounds().center();
+        let transform = |pos: egui::Pos2| -> egui::Pos2 { to_screen(rotate_point(pos, pivot, rotation)) };
+        let rotated_quad = |rect: &egui::Rect| -> Vec<egui::Pos2> {
+            [rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom()]
+                .into_iter()
+                .map(transform)
+                .collect()
+        };
+        let draw_galley = |pos: egui::Pos2, galley: Arc<egui::Galley>, color: egui::Color32| {
+            painter.add(egui::epaint::TextShape::new(pos, galley, color).with_angle(rotation));
+        };
 
         match self {
             ShapeData::Pen { points, color, stroke_width } => {
@@ -640,15 +844,19 @@ impl ShapeData {
                 painter.line_segment([transform(*start), transform(*end)], stroke);
             }
             ShapeData::Rectangle { rect, color, stroke_width, filled } => {
-                let start = transform(rect.min);
-                let end = transform(rect.max);
-                let transformed_rect = egui::Rect::from_two_pos(start, end);
                 let fill = if *filled { *color } else { egui::Color32::TRANSPARENT };
                 let stroke = egui::Stroke::new(stroke_width * zoom, *color);
-                painter.rect(transformed_rect, 0.0, fill, stroke, egui::StrokeKind::Outside);
+                if rotation != 0.0 {
+                    painter.add(egui::Shape::convex_polygon(rotated_quad(rect), fill, stroke));
+                } else {
+                    let start = to_screen(rect.min);
+                    let end = to_screen(rect.max);
+                    let transformed_rect = egui::Rect::from_two_pos(start, end);
+                    painter.rect(transformed_rect, 0.0, fill, stroke, egui::StrokeKind::Outside);
+                }
             }
             ShapeData::Circle { center, radius, color, stroke_width, filled } => {
-                let center_transformed = transform(*center);
+                let center_transformed = to_screen(*center);
                 let radius_transformed = radius * zoom;
                 let fill = if *filled { *color } else { egui::Color32::TRANSPARENT };
                 let stroke = egui::Stroke::new(stroke_width * zoom, *color);
@@ -658,63 +866,52 @@ impl ShapeData {
                 let screen_pos = transform(*pos);
                 if let Some(lt) = link_title {
                     let font_id = egui::FontId::proportional(13.0 * zoom);
-                    let title_pos = screen_pos - egui::vec2(0.0, 18.0 * zoom);
+                    let title_pos = transform(*pos - egui::vec2(0.0, 18.0));
                     let text_color = egui::Color32::from_rgb(147, 197, 253);
                     let galley = painter.layout_no_wrap(lt.clone(), font_id, text_color);
-                    painter.galley(title_pos, galley, text_color);
+                    draw_galley(title_pos, galley, text_color);
                 }
                 if is_editing {
                     return;
                 }
                 let font_id = egui::FontId::proportional(*size * zoom);
-                let bold_offset = egui::vec2((0.4 * zoom).max(0.4), 0.0);
-                if let Some(mw) = max_width {
-                    let wrap_width = mw * zoom;
-                    let galley =
-                        painter.layout(text.clone(), font_id.clone(), *color, wrap_width);
-                    painter.galley(screen_pos, galley.clone(), *color);
-                    painter.galley(screen_pos + bold_offset, galley, *color);
+                let bold_offset = egui::emath::Rot2::from_angle(rotation)
+                    * egui::vec2((0.4 * zoom).max(0.4), 0.0);
+                let galley = if let Some(mw) = max_width {
+                    painter.layout(text.clone(), font_id, *color, mw * zoom)
                 } else {
-                    painter.text(
-                        screen_pos,
-                        egui::Align2::LEFT_TOP,
-                        text,
-                        font_id.clone(),
-                        *color,
-                    );
-                    painter.text(
-                        screen_pos + bold_offset,
-                        egui::Align2::LEFT_TOP,
-                        text,
-                        font_id,
-                        *color,
-                    );
-                }
+                    painter.layout_no_wrap(text.clone(), font_id, *color)
+                };
+                draw_galley(screen_pos, galley.clone(), *color);
+                draw_galley(screen_pos + bold_offset, galley, *color);
             }
             ShapeData::Image { rect, texture, .. } => {
                 if let Some(tex) = texture {
-                    let start = transform(rect.min);
-                    let end = transform(rect.max);
+                    let start = to_screen(rect.min);
+                    let end = to_screen(rect.max);
                     let transformed_rect = egui::Rect::from_two_pos(start, end);
-                    painter.image(
-                        tex.id(),
-                        transformed_rect,
-                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
-                        egui::Color32::WHITE,
-                    );
+                    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                    if rotation != 0.0 {
+                        let mut mesh = egui::Mesh::with_texture(tex.id());
+                        mesh.add_rect_with_uv(transformed_rect, uv, egui::Color32::WHITE);
+                        mesh.rotate(egui::emath::Rot2::from_angle(rotation), to_screen(pivot));
+                        painter.add(mesh);
+                    } else {
+                        painter.image(tex.id(), transformed_rect, uv, egui::Color32::WHITE);
+                    }
                 }
             }
             ShapeData::StickyNote { rect, text, bg_color, text_color, text_size, link_title, .. } => {
-                let start = transform(rect.min);
-                let end = transform(rect.max);
+                let start = to_screen(rect.min);
+                let end = to_screen(rect.max);
                 let transformed_rect = egui::Rect::from_two_pos(start, end);
 
                 if let Some(lt) = link_title {
                     let font_id = egui::FontId::proportional(13.0 * zoom);
-                    let title_pos = transformed_rect.left_top() - egui::vec2(0.0, 18.0 * zoom);
+                    let title_pos = transform(rect.min - egui::vec2(0.0, 18.0));
                     let text_color = egui::Color32::from_rgb(147, 197, 253);
                     let galley = painter.layout_no_wrap(lt.clone(), font_id, text_color);
-                    painter.galley(title_pos, galley, text_color);
+                    draw_galley(title_pos, galley, text_color);
                 }
                 
                 let dark_mode = painter.ctx().style().visuals.dark_mode;
@@ -723,15 +920,24 @@ impl ShapeData {
                 } else {
                     (*bg_color, *text_color)
                 };
+                let border = if dark_mode {
+                    egui::Stroke::new(1.0 * zoom, egui::Color32::from_rgb(65, 60, 45))
+                } else {
+                    egui::Stroke::NONE
+                };
 
-                painter.rect_filled(transformed_rect, 6.0 * zoom, draw_bg);
-                if dark_mode {
-                    painter.rect_stroke(
-                        transformed_rect,
-                        6.0 * zoom,
-                        egui::Stroke::new(1.0 * zoom, egui::Color32::from_rgb(65, 60, 45)),
-                        egui::StrokeKind::Outside,
-                    );
+                if rotation != 0.0 {
+                    painter.add(egui::Shape::convex_polygon(rotated_quad(rect), draw_bg, border));
+                } else {
+                    painter.rect_filled(transformed_rect, 6.0 * zoom, draw_bg);
+                    if dark_mode {
+                        painter.rect_stroke(
+                            transformed_rect,
+                            6.0 * zoom,
+                            border,
+                            egui::StrokeKind::Outside,
+                        );
+                    }
                 }
                 if !is_editing {
                     let padding = 8.0 * zoom;
@@ -739,13 +945,13 @@ impl ShapeData {
                     if text_rect.width() > 0.0 && text_rect.height() > 0.0 {
                         let font_id = egui::FontId::proportional(*text_size * zoom);
                         let galley = painter.layout(text.clone(), font_id, draw_text, text_rect.width());
-                        painter.galley(text_rect.min.into(), galley, draw_text);
+                        draw_galley(transform(rect.min + egui::vec2(8.0, 8.0)), galley, draw_text);
                     }
                 }
             }
             ShapeData::SectionBox { rect, color } => {
-                let start = transform(rect.min);
-                let end = transform(rect.max);
+                let start = to_screen(rect.min);
+                let end = to_screen(rect.max);
                 let transformed_rect = egui::Rect::from_two_pos(start, end);
                 let fill = egui::Color32::from_rgba_unmultiplied(
                     color.r(),
@@ -753,13 +959,35 @@ impl ShapeData {
                     color.b(),
                     18,
                 );
-                painter.rect_filled(transformed_rect, 4.0 * zoom, fill);
-                painter.rect_stroke(
-                    transformed_rect,
-                    4.0 * zoom,
-                    egui::Stroke::new(1.5, *color),
-                    egui::StrokeKind::Inside,
-                );
+                let stroke = egui::Stroke::new(1.5, *color);
+                if rotation != 0.0 {
+                    painter.add(egui::Shape::convex_polygon(rotated_quad(rect), fill, stroke));
+                } else {
+                    painter.rect_filled(transformed_rect, 4.0 * zoom, fill);
+                    painter.rect_stroke(
+                        transformed_rect,
+                        4.0 * zoom,
+                        stroke,
+                        egui::StrokeKind::Inside,
+                    );
+                }
+            }
+            ShapeData::Connector { start, end, color, stroke_width, arrows, label, .. } => {
+                let (line, heads) = connector::connector_geometry(*start, *end, *stroke_width, *arrows);
+                let stroke = egui::Stroke::new(stroke_width * zoom, *color);
+                painter.line_segment(line.map(transform), stroke);
+                for head in heads {
+                    painter.add(egui::Shape::convex_polygon(
+                        head.map(transform).to_vec(),
+                        *color,
+                        egui::Stroke::NONE,
+                    ));
+                }
+                if !label.is_empty() && !is_editing {
+                    let font_id = egui::FontId::proportional(connector::LABEL_SIZE * zoom);
+                    let galley = painter.layout_no_wrap(label.clone(), font_id, *color);
+                    draw_galley(transform(connector::label_pos(*start, *end)), galley, *color);
+                }
             }
         }
     }
@@ -800,7 +1028,7 @@ mod tests {
     #[test]
     fn test_image_resize_aspect_ratio() {
         let rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(100.0, 50.0));
-        let mut shape = Shape::new_image(1, rect, vec![], [100.0, 50.0], None);
+        let mut shape = Shape::new_image(1, rect, Arc::from(Vec::new()), [100.0, 50.0], None);
         
         shape.data.resize(3, egui::vec2(100.0, 100.0), egui::pos2(200.0, 200.0));
         
@@ -830,4 +1058,60 @@ mod tests {
             panic!("Expected Rectangle");
         }
     }
+
+    #[test]
+    fn test_rotated_bounds_and_hit_test() {
+        let rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(100.0, 20.0));
+        let mut shape = Shape::new_rect(1, rect, egui::Color32::WHITE, 1.0, true);
+        shape.rotation = std::f32::consts::FRAC_PI_2;
+
+        let bounds = shape.get_bounds();
+        assert!((bounds.min.x - 40.0).abs() < 1e-3 && (bounds.max.x - 60.0).abs() < 1e-3);
+        assert!((bounds.min.y + 40.0).abs() < 1e-3 && (bounds.max.y - 60.0).abs() < 1e-3);
+        // Turned upright, the far left of the original rect is empty space
+        assert!(!shape.contains_point(egui::pos2(5.0, 10.0), 2.0));
+        assert!(shape.contains_point(egui::pos2(50.0, -35.0), 2.0));
+    }
+
+    #[test]
+    fn test_rotated_resize_keeps_opposite_corner() {
+        let rect = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(100.0, 50.0));
+        let mut shape = Shape::new_rect(1, rect, egui::Color32::WHITE, 1.0, false);
+        shape.rotation = 0.5;
+        let anchor = shape.corners()[0];
+        let target = shape.corners()[3] + egui::vec2(30.0, 10.0);
+
+        shape.resize(3, egui::vec2(30.0, 10.0), target);
+
+        assert!(shape.corners()[0].distance(anchor) < 1e-3);
+        assert!(shape.corners()[3].distance(target) < 1e-3);
+    }
+
+    #[test]
+    fn test_rotate_about_moves_center_and_wraps_angle() {
+        let rect = egui::Rect::from_center_size(egui::pos2(10.0, 0.0), egui::vec2(4.0, 4.0));
+        let mut shape = Shape::new_rect(1, rect, egui::Color32::WHITE, 1.0, false);
+        shape.rotation = 3.0;
+
+        shape.rotate_about(egui::pos2(0.0, 0.0), std::f32::consts::FRAC_PI_2);
+
+        assert!(shape.pivot().distance(egui::pos2(0.0, 10.0)) < 1e-3);
+        let expected = 3.0 + std::f32::consts::FRAC_PI_2 - std::f32::consts::TAU;
+        assert!((shape.rotation - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_opacity_and_blend_are_optional_in_saved_shapes() {
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(4.0, 4.0));
+        let mut shape = Shape::new_rect(1, rect, egui::Color32::WHITE, 1.0, true);
+        let json = serde_json::to_string(&shape).unwrap();
+        assert!(!json.contains("opacity") && !json.contains("blend"));
+        let loaded: Shape = serde_json::from_str(&json).unwrap();
+        assert_eq!((loaded.opacity, loaded.blend), (1.0, BlendMode::Normal));
+
+        shape.opacity = 0.4;
+        shape.blend = BlendMode::Screen;
+        let loaded: Shape = serde_json::from_str(&serde_json::to_string(&shape).unwrap()).unwrap();
+        assert_eq!((loaded.opacity, loaded.blend), (0.4, BlendMode::Screen));
+    }
 }
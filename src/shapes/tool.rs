@@ -12,4 +12,5 @@ pub enum Tool {
     Text,
     StickyNote,
     Section,
+    Connector,
 }
@@ -0,0 +1,147 @@
+use crate::state::container::write_board;
+use crate::state::CanvasState;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of previous versions kept next to a board as `.kugel.bak` files.
+pub const BACKUP_COUNT: usize = 5;
+
+/// Previous version of a board, `board.1.kugel.bak` being the most recent.
+pub struct Backup {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// `board.kugel` -> `board.<n>.kugel.bak`
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.{}.kugel.bak", stem, n))
+}
+
+/// Write a board to a temp file next to `path`, sync it and rename it into
+/// place, so a crash or full disk never leaves a half-written board behind.
+pub fn write_atomic(path: &Path, state: CanvasState) -> Result<(), String> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let result = (|| -> Result<(), String> {
+        let file = std::fs::File::create(&tmp).map_err(|e| e.to_string())?;
+        let mut writer = BufWriter::new(file);
+        write_board(&mut writer, state)?;
+        let file = writer.into_inner().map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Save a board atomically, first shifting the version on disk into the
+/// `.kugel.bak` rotation and dropping the oldest backup. Backups are best
+/// effort: a failed rotation does not block the save and comes back as
+/// `Ok(Some(error))`.
+pub fn save_with_backups(path: &Path, state: CanvasState) -> Result<Option<String>, String> {
+    let backup_error = if path.is_file() {
+        rotate_backups(path).err().map(|e| e.to_string())
+    } else {
+        None
+    };
+    write_atomic(path, state)?;
+    Ok(backup_error)
+}
+
+fn rotate_backups(path: &Path) -> std::io::Result<()> {
+    let oldest = backup_path(path, BACKUP_COUNT);
+    if oldest.exists() {
+        std::fs::remove_file(&oldest)?;
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    // Copy rather than rename so the board itself never goes missing. The
+    // copy gets the board's modified time, which is when that version was saved.
+    let newest = backup_path(path, 1);
+    std::fs::copy(path, &newest)?;
+    let saved = std::fs::metadata(path)?.modified()?;
+    std::fs::File::options().write(true).open(&newest)?.set_modified(saved)
+}
+
+/// Existing backups of a board, newest first.
+pub fn list_backups(path: &Path) -> Vec<Backup> {
+    (1..=BACKUP_COUNT)
+        .map(|n| backup_path(path, n))
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some(Backup { path, modified })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::container::read_board;
+    use crate::state::FORMAT_VERSION;
+
+    fn state(next_id: usize) -> CanvasState {
+        CanvasState {
+            version: FORMAT_VERSION.to_string(),
+            shapes: Vec::new(),
+            background_color: [0, 0, 0, 255],
+            zoom: 1.0,
+            pan_offset: [0.0, 0.0],
+            next_id,
+            dark_mode: true,
+        }
+    }
+
+    fn read_next_id(path: &Path) -> usize {
+        read_board(&std::fs::read(path).unwrap()).unwrap().next_id
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path(Path::new("/boards/ideas.kugel"), 2),
+            PathBuf::from("/boards/ideas.2.kugel.bak")
+        );
+    }
+
+    #[test]
+    fn test_save_rotates_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("board.kugel");
+        for id in 0..=BACKUP_COUNT + 1 {
+            assert_eq!(save_with_backups(&path, state(id)), Ok(None));
+        }
+
+        assert_eq!(read_next_id(&path), BACKUP_COUNT + 1);
+        let backups = list_backups(&path);
+        assert_eq!(backups.len(), BACKUP_COUNT);
+        assert_eq!(read_next_id(&backups[0].path), BACKUP_COUNT);
+        assert_eq!(read_next_id(&backups[BACKUP_COUNT - 1].path), 1);
+        assert!(!dir.path().join("board.kugel.tmp").exists());
+    }
+
+    #[test]
+    fn test_backup_keeps_save_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("board.kugel");
+        save_with_backups(&path, state(1)).unwrap();
+        let saved = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(saved).unwrap();
+
+        save_with_backups(&path, state(2)).unwrap();
+        assert_eq!(list_backups(&path)[0].modified, saved);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::assets::content_hash;
+use crate::shapes::{Shape, ShapeData};
+use crate::state::error::LoadError;
+use crate::state::migrate::migrate;
+use crate::state::{CanvasState, FORMAT_VERSION};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Board geometry lives in this entry; every image is stored next to it as
+/// `images/<sha256>` so the JSON stays small and fast to parse.
+const STATE_ENTRY: &str = "state.json";
+const IMAGE_DIR: &str = "images/";
+
+/// Every zip archive starts with a local file header signature.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(ZIP_MAGIC)
+}
+
+/// Write `state` as a zip container. Image bytes are moved out of the shapes
+/// into their own stored (uncompressed) entries; identical images share one entry.
+pub fn write_board<W: Write + Seek>(writer: W, mut state: CanvasState) -> Result<(), String> {
+    let mut images: Vec<(String, Arc<[u8]>)> = Vec::new();
+    for shape in &mut state.shapes {
+        if let ShapeData::Image { bytes, asset, .. } = &mut shape.data {
+            let hash = asset.clone().unwrap_or_else(|| content_hash(bytes));
+            if !images.iter().any(|(h, _)| *h == hash) {
+                images.push((hash.clone(), bytes.clone()));
+            }
+            *bytes = Arc::from(Vec::new());
+            *asset = Some(hash);
+        }
+    }
+    state.version = FORMAT_VERSION.to_string();
+
+    let json = serde_json::to_vec_pretty(&state).map_err(|e| e.to_string())?;
+
+    let mut zip = zip::ZipWriter::new(writer);
+    let deflated = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    // Images are already PNG/JPEG compressed, deflating them again only costs time.
+    let stored = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+
+    zip.start_file(STATE_ENTRY, deflated)
+        .map_err(|e| e.to_string())?;
+    zip.write_all(&json).map_err(|e| e.to_string())?;
+
+    for (hash, bytes) in &images {
+        zip.start_file(format!("{}{}", IMAGE_DIR, hash), stored)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())?;
+    }
+
+    let mut inner = zip.finish().map_err(|e| e.to_string())?;
+    inner.flush().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Parse board JSON of any known version, upgrading it to the current format.
+/// Shapes are deserialized and passed through `resolve` one by one, so a
+/// broken shape is reported by its position and skipped when `salvage` is set.
+fn parse_state(
+    json: &[u8],
+    salvage: bool,
+    resolve: &mut dyn FnMut(usize, &mut Shape) -> Result<(), LoadError>,
+) -> Result<(CanvasState, Vec<LoadError>), LoadError> {
+    let doc = serde_json::from_slice::<Value>(json).map_err(LoadError::from_json)?;
+    let mut doc = migrate(doc).map_err(LoadError::Format)?;
+
+    let raw_shapes = match doc.get_mut("shapes").map(Value::take) {
+        Some(Value::Array(shapes)) => shapes,
+        _ => return Err(LoadError::Format("Board has no shape list".to_string())),
+    };
+    doc["shapes"] = Value::Array(Vec::new());
+    let mut state =
+        serde_json::from_value::<CanvasState>(doc).map_err(|e| LoadError::Format(e.to_string()))?;
+
+    let mut dropped = Vec::new();
+    for (index, raw) in raw_shapes.into_iter().enumerate() {
+        let variant = raw
+            .get("data")
+            .and_then(Value::as_object)
+            .and_then(|data| data.keys().next().cloned())
+            .unwrap_or_default();
+        let shape = serde_json::from_value::<Shape>(raw)
+            .map_err(|e| {
+                let message = e.to_string();
+                if message.starts_with("unknown variant") {
+                    LoadError::UnknownShape { index, variant }
+                } else if variant == "Image" {
+                    LoadError::BadImage {
+                        index,
+                        reason: message,
+                    }
+                } else {
+                    LoadError::InvalidShape { index, message }
+                }
+            })
+            .and_then(|mut shape| resolve(index, &mut shape).map(|_| shape));
+        match shape {
+            Ok(shape) => state.shapes.push(shape),
+            Err(e) if salvage => dropped.push(e),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((state, dropped))
+}
+
+/// Read a board from raw file bytes. Both the zip container and the legacy
+/// 1.0 plain JSON format (with inline base64 images) are accepted.
+pub fn read_board(bytes: &[u8]) -> Result<CanvasState, LoadError> {
+    read(bytes, false).map(|(state, _)| state)
+}
+
+/// Like `read_board`, but shapes that fail to load are left out and returned
+/// alongside the board instead of failing the whole read.
+pub fn read_board_salvaged(bytes: &[u8]) -> Result<(CanvasState, Vec<LoadError>), LoadError> {
+    read(bytes, true)
+}
+
+pub fn load_board(path: &Path) -> Result<CanvasState, LoadError> {
+    read_board(&std::fs::read(path)?)
+}
+
+fn read(bytes: &[u8], salvage: bool) -> Result<(CanvasState, Vec<LoadError>), LoadError> {
+    if !is_container(bytes) {
+        return parse_state(bytes, salvage, &mut |_, _| Ok(()));
+    }
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| LoadError::Archive(e.to_string()))?;
+
+    let mut json = Vec::new();
+    archive
+        .by_name(STATE_ENTRY)
+        .map_err(|e| LoadError::Archive(format!("{}: {}", STATE_ENTRY, e)))?
+        .read_to_end(&mut json)
+        .map_err(|e| LoadError::Archive(format!("{}: {}", STATE_ENTRY, e)))?;
+
+    let mut loaded: HashMap<String, Arc<[u8]>> = HashMap::new();
+    parse_state(&json, salvage, &mut |index, shape| {
+        let ShapeData::Image {
+            bytes: image_bytes,
+            asset: Some(hash),
+            ..
+        } = &mut shape.data
+        else {
+            return Ok(());
+        };
+        if let Some(cached) = loaded.get(hash.as_str()) {
+            *image_bytes = cached.clone();
+            return Ok(());
+        }
+        let mut data = Vec::new();
+        archive
+            .by_name(&format!("{}{}", IMAGE_DIR, hash))
+            .map_err(|e| e.to_string())
+            .and_then(|mut entry| entry.read_to_end(&mut data).map_err(|e| e.to_string()))
+            .map_err(|e| LoadError::BadImage {
+                index,
+                reason: format!("image {}: {}", hash, e),
+            })?;
+        let data: Arc<[u8]> = data.into();
+        loaded.insert(hash.clone(), data.clone());
+        *image_bytes = data;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Shape;
+    use eframe::egui;
+
+    fn board_with_images(images: Vec<Vec<u8>>) -> CanvasState {
+        let shapes = images
+            .into_iter()
+            .enumerate()
+            .map(|(i, bytes)| {
+                let rect = egui::Rect::from_min_size(egui::pos2(i as f32 * 20.0, 0.0), egui::vec2(10.0, 10.0));
+                Shape::new_image(i, rect, bytes.into(), [10.0, 10.0], None)
+            })
+            .collect::<Vec<_>>();
+        CanvasState {
+            version: "1.0".to_string(),
+            next_id: shapes.len(),
+            shapes,
+            background_color: [0, 0, 0, 255],
+            zoom: 1.0,
+            pan_offset: [0.0, 0.0],
+            dark_mode: true,
+        }
+    }
+
+    #[test]
+    fn test_container_roundtrip_dedups_images() {
+        let state = board_with_images(vec![vec![1, 2, 3], vec![1, 2, 3], vec![9, 9]]);
+        let mut buf = Cursor::new(Vec::new());
+        write_board(&mut buf, state).unwrap();
+        let bytes = buf.into_inner();
+        assert!(is_container(&bytes));
+
+        let archive = zip::ZipArchive::new(Cursor::new(bytes.as_slice())).unwrap();
+        let image_entries = archive.file_names().filter(|n| n.starts_with(IMAGE_DIR)).count();
+        assert_eq!(image_entries, 2);
+
+        let loaded = read_board(&bytes).unwrap();
+        assert_eq!(loaded.version, FORMAT_VERSION);
+        let loaded_bytes: Vec<Vec<u8>> = loaded
+            .shapes
+            .iter()
+            .filter_map(|s| match &s.data {
+                ShapeData::Image { bytes, .. } => Some(bytes.to_vec()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(loaded_bytes, vec![vec![1, 2, 3], vec![1, 2, 3], vec![9, 9]]);
+    }
+
+    #[test]
+    fn test_read_legacy_json_board() {
+        let json = r#"{"version":"1.0","shapes":[{"id":0,"data":{"Image":{"rect":{"min":{"x":0.0,"y":0.0},"max":{"x":10.0,"y":10.0}},"bytes":"AQID","original_size":[10.0,10.0]}}}],"background_color":[0,0,0,255],"zoom":1.0,"pan_offset":[0.0,0.0],"next_id":1}"#;
+        let state = read_board(json.as_bytes()).unwrap();
+        assert_eq!(state.version, FORMAT_VERSION);
+        match &state.shapes[0].data {
+            ShapeData::Image { bytes, asset, .. } => {
+                assert_eq!(&bytes[..], &[1, 2, 3]);
+                assert!(asset.is_none());
+            }
+            _ => panic!("Expected Image"),
+        }
+    }
+
+    #[test]
+    fn test_json_syntax_error_reports_position() {
+        let Err(err) = read_board(b"{\n  \"version\": \"1.0\",\n  \"shapes\": [,]\n}") else {
+            panic!("Expected an error");
+        };
+        match err {
+            LoadError::Json { line, column, .. } => assert_eq!((line, column), (3, 14)),
+            other => panic!("Expected Json error, got {}", other),
+        }
+    }
+
+    #[test]
+    fn test_salvage_drops_unknown_shapes() {
+        let json = r#"{"version":"2.0","shapes":[{"id":0,"data":{"Blob":{"size":3}}},{"id":1,"data":{"Line":{"start":{"x":0.0,"y":0.0},"end":{"x":1.0,"y":1.0},"color":[255,255,255,255],"stroke_width":2.0}}}],"background_color":[0,0,0,255],"zoom":1.0,"pan_offset":[0.0,0.0],"next_id":2}"#;
+        match read_board(json.as_bytes()) {
+            Err(LoadError::UnknownShape { index, variant }) => {
+                assert_eq!(index, 0);
+                assert_eq!(variant, "Blob");
+            }
+            _ => panic!("Expected UnknownShape"),
+        }
+
+        let (state, dropped) = read_board_salvaged(json.as_bytes()).unwrap();
+        assert_eq!(state.shapes.len(), 1);
+        assert_eq!(state.shapes[0].id, 1);
+        assert_eq!(dropped.len(), 1);
+        assert!(dropped[0].is_shape_error());
+    }
+
+    #[test]
+    fn test_salvage_drops_images_missing_from_container() {
+        let mut buf = Cursor::new(Vec::new());
+        {
+            let mut state = board_with_images(vec![vec![1, 2, 3]]);
+            if let ShapeData::Image { bytes, asset, .. } = &mut state.shapes[0].data {
+                *asset = Some(content_hash(bytes));
+                *bytes = Arc::from(Vec::new());
+            }
+            let mut zip = zip::ZipWriter::new(&mut buf);
+            zip.start_file(STATE_ENTRY, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(&serde_json::to_vec(&state).unwrap()).unwrap();
+            zip.finish().unwrap();
+        }
+        let bytes = buf.into_inner();
+
+        assert!(matches!(read_board(&bytes), Err(LoadError::BadImage { index: 0, .. })));
+        let (state, dropped) = read_board_salvaged(&bytes).unwrap();
+        assert!(state.shapes.is_empty());
+        assert_eq!(dropped.len(), 1);
+    }
+}
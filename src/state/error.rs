@@ -0,0 +1,90 @@
+use std::fmt;
+
+/// Why a board could not be read. The shape-level variants only affect a
+/// single shape and can be skipped by `container::read_board_salvaged`.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// The zip container is damaged or misses its board entry.
+    Archive(String),
+    Json {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Valid JSON that is not a board this version of Kugel understands.
+    Format(String),
+    UnknownShape {
+        index: usize,
+        variant: String,
+    },
+    BadImage {
+        index: usize,
+        reason: String,
+    },
+    InvalidShape {
+        index: usize,
+        message: String,
+    },
+}
+
+impl LoadError {
+    /// True when the error is confined to one shape and the rest of the board
+    /// can still be loaded.
+    pub fn is_shape_error(&self) -> bool {
+        matches!(
+            self,
+            LoadError::UnknownShape { .. }
+                | LoadError::BadImage { .. }
+                | LoadError::InvalidShape { .. }
+        )
+    }
+
+    pub fn from_json(err: serde_json::Error) -> Self {
+        let message = err.to_string();
+        // serde_json appends the position, which is reported separately.
+        let message = match message.rfind(" at line ") {
+            Some(pos) => message[..pos].to_string(),
+            None => message,
+        };
+        LoadError::Json {
+            line: err.line(),
+            column: err.column(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "could not read file: {}", e),
+            LoadError::Archive(msg) => write!(f, "damaged board file: {}", msg),
+            LoadError::Json {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid board data at line {}, column {}: {}",
+                line, column, message
+            ),
+            LoadError::Format(msg) => write!(f, "{}", msg),
+            LoadError::UnknownShape { index, variant } => {
+                write!(f, "shape {} has unknown type \"{}\"", index + 1, variant)
+            }
+            LoadError::BadImage { index, reason } => {
+                write!(f, "image shape {} has unreadable data: {}", index + 1, reason)
+            }
+            LoadError::InvalidShape { index, message } => {
+                write!(f, "shape {} is invalid: {}", index + 1, message)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(err: std::io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
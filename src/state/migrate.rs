@@ -0,0 +1,124 @@
+use crate::state::FORMAT_VERSION;
+use serde_json::Value;
+
+/// One upgrade step, taking a document from `major` to `major + 1`.
+type Step = fn(&mut Value) -> Result<(), String>;
+
+/// Ordered upgrade steps keyed by the major version they start from.
+/// Add a new entry here whenever `FORMAT_VERSION` gets a major bump.
+const STEPS: &[(u32, Step)] = &[(1, v1_to_v2)];
+
+/// Parse a "major.minor" board version. A bare "major" is accepted as well.
+pub fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().splitn(2, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = match parts.next() {
+        Some(m) => m.parse().ok()?,
+        None => 0,
+    };
+    Some((major, minor))
+}
+
+fn current_version() -> (u32, u32) {
+    parse_version(FORMAT_VERSION).expect("FORMAT_VERSION must be a valid version")
+}
+
+/// Detect the version of a raw board document and upgrade it step by step to
+/// the current format. Boards from a newer major version are refused, newer
+/// minor versions only add optional fields and are read as-is.
+pub fn migrate(mut doc: Value) -> Result<Value, String> {
+    let raw_version = match doc.get("version") {
+        Some(Value::String(v)) => v.clone(),
+        // Boards written before the version field existed.
+        None | Some(Value::Null) => "1.0".to_string(),
+        Some(other) => return Err(format!("Unrecognized board version: {}", other)),
+    };
+    let (major, minor) = parse_version(&raw_version)
+        .ok_or_else(|| format!("Unrecognized board version: {}", raw_version))?;
+    let (current_major, current_minor) = current_version();
+
+    if major > current_major {
+        return Err(format!(
+            "This board was saved by a newer version of Kugel (format {}). Update Kugel to open it.",
+            raw_version
+        ));
+    }
+
+    for from in major..current_major {
+        let step = STEPS
+            .iter()
+            .find(|(step_from, _)| *step_from == from)
+            .map(|(_, step)| step)
+            .ok_or_else(|| format!("No migration available from board format {}", from))?;
+        step(&mut doc)?;
+    }
+
+    if major < current_major || minor < current_minor {
+        set_version(&mut doc, FORMAT_VERSION);
+    }
+    Ok(doc)
+}
+
+fn set_version(doc: &mut Value, version: &str) {
+    if let Some(obj) = doc.as_object_mut() {
+        obj.insert("version".to_string(), Value::String(version.to_string()));
+    }
+}
+
+/// 1.x boards are plain JSON with inline base64 images. The shape layout is
+/// unchanged in 2.0; only board-level fields added since then are filled in.
+fn v1_to_v2(doc: &mut Value) -> Result<(), String> {
+    let obj = doc
+        .as_object_mut()
+        .ok_or_else(|| "Board file is not a JSON object".to_string())?;
+    obj.entry("dark_mode").or_insert(Value::Bool(true));
+    obj.entry("shapes").or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.0"), Some((1, 0)));
+        assert_eq!(parse_version("2.13"), Some((2, 13)));
+        assert_eq!(parse_version("3"), Some((3, 0)));
+        assert_eq!(parse_version("abc"), None);
+    }
+
+    #[test]
+    fn test_migrate_v1_board_to_current() {
+        let doc = json!({"version": "1.0", "shapes": [], "background_color": [0, 0, 0, 255], "zoom": 1.0, "pan_offset": [0.0, 0.0], "next_id": 0});
+        let migrated = migrate(doc).unwrap();
+        assert_eq!(migrated["version"], FORMAT_VERSION);
+        assert_eq!(migrated["dark_mode"], true);
+    }
+
+    #[test]
+    fn test_migrate_missing_version_is_v1() {
+        let doc = json!({"shapes": [], "background_color": [0, 0, 0, 255], "zoom": 1.0, "pan_offset": [0.0, 0.0], "next_id": 0, "dark_mode": false});
+        let migrated = migrate(doc).unwrap();
+        assert_eq!(migrated["version"], FORMAT_VERSION);
+        assert_eq!(migrated["dark_mode"], false);
+    }
+
+    #[test]
+    fn test_migrate_refuses_newer_major() {
+        let (major, _) = current_version();
+        let doc = json!({"version": format!("{}.0", major + 1), "shapes": []});
+        let err = migrate(doc).unwrap_err();
+        assert!(err.contains("newer version of Kugel"));
+    }
+
+    #[test]
+    fn test_migrate_accepts_newer_minor() {
+        let (major, minor) = current_version();
+        let version = format!("{}.{}", major, minor + 1);
+        let doc = json!({"version": version.clone(), "shapes": []});
+        let migrated = migrate(doc).unwrap();
+        assert_eq!(migrated["version"], version);
+    }
+}
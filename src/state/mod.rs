@@ -1,6 +1,16 @@
+pub mod backup;
+pub mod container;
+pub mod error;
+pub mod migrate;
+
 use crate::shapes::Shape;
 use serde::{Deserialize, Serialize};
 
+/// Version written into every saved board. "1.0" boards are plain JSON with
+/// inline base64 images; "2.0" boards are zip containers (see `container`).
+/// Bump the major version for breaking changes and add a step in `migrate`.
+pub const FORMAT_VERSION: &str = "2.0";
+
 #[derive(Serialize, Deserialize)]
 pub struct CanvasState {
     pub version: String,
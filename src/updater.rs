@@ -16,6 +16,25 @@ pub enum UiEvent {
         url: String,
         title: String,
     },
+    AutosaveFinished {
+        path: std::path::PathBuf,
+        error: Option<String>,
+    },
+    ThumbnailRendered(std::path::PathBuf),
+    ExportProgress {
+        done: usize,
+        total: usize,
+    },
+    /// Toast text for a finished export: a summary, or the failure.
+    ExportFinished {
+        result: Result<String, String>,
+    },
+    /// Copy as PNG pixels rendered off the UI thread, to be put on the
+    /// clipboard there.
+    CopyImageRendered {
+        size: [u32; 2],
+        pixels: Result<Vec<u8>, String>,
+    },
 }
 
 #[derive(Default, Clone)]